@@ -0,0 +1,111 @@
+//! Concurrent-load benchmark for `SqliteItemService`.
+//!
+//! Runs the same burst of parallel `list_all`/`create` calls twice against a
+//! file-backed pool: once awaiting the service directly, and once wrapping
+//! every call in `block_in_place` + `block_on`, which is how handlers drove
+//! the database before the service traits became async.
+//!
+//! Run with `cargo bench --bench item_service`.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use app::services::items::{ItemChanges, SqliteItemService};
+use app::services::ItemService;
+use tokio::runtime::Handle;
+use tokio::task::{block_in_place, JoinSet};
+
+/// Parallel requests per round
+const CONCURRENCY: usize = 64;
+/// Rounds per mode; the best round is reported
+const ROUNDS: usize = 10;
+/// Items created before measuring so `list_all` has rows to read
+const SEED_ITEMS: usize = 200;
+
+#[derive(Clone, Copy)]
+enum Mode {
+    Async,
+    BlockInPlace,
+}
+
+fn changes(i: usize) -> ItemChanges {
+    ItemChanges {
+        title: format!("Item {i}"),
+        description: "benchmark".into(),
+        tags: Vec::new(),
+        due_at: None,
+        parent_id: None,
+    }
+}
+
+/// One request: every fourth call creates an item, the rest list them all.
+async fn request(service: &SqliteItemService, i: usize) {
+    if i.is_multiple_of(4) {
+        service.create(changes(i), "bench").await.unwrap();
+    } else {
+        service.list_all().await.unwrap();
+    }
+}
+
+async fn round(service: Arc<SqliteItemService>, mode: Mode, offset: usize) -> Duration {
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for i in offset..offset + CONCURRENCY {
+        let service = service.clone();
+        tasks.spawn(async move {
+            match mode {
+                Mode::Async => request(&service, i).await,
+                Mode::BlockInPlace => {
+                    block_in_place(|| Handle::current().block_on(request(&service, i)))
+                }
+            }
+        });
+    }
+    while let Some(joined) = tasks.join_next().await {
+        joined.unwrap();
+    }
+    start.elapsed()
+}
+
+async fn run(mode: Mode) -> Duration {
+    let path = std::env::temp_dir().join(format!("item-bench-{}.db", uuid::Uuid::new_v4()));
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let pool = app::db::init_pool(&url).await.unwrap();
+    let service = Arc::new(SqliteItemService::new(pool.clone()));
+    for i in 0..SEED_ITEMS {
+        service.create(changes(i), "bench").await.unwrap();
+    }
+
+    let mut best = Duration::MAX;
+    for r in 0..ROUNDS {
+        best = best.min(round(service.clone(), mode, SEED_ITEMS + r * CONCURRENCY).await);
+    }
+
+    pool.close().await;
+    let _ = std::fs::remove_file(&path);
+    best
+}
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let blocking = runtime.block_on(run(Mode::BlockInPlace));
+    let awaited = runtime.block_on(run(Mode::Async));
+    let per_sec = |d: Duration| CONCURRENCY as f64 / d.as_secs_f64();
+
+    println!("{CONCURRENCY} concurrent list_all/create calls, best of {ROUNDS} rounds");
+    println!(
+        "  block_in_place: {:>8.2} ms  ({:>7.0} req/s)",
+        blocking.as_secs_f64() * 1e3,
+        per_sec(blocking)
+    );
+    println!(
+        "  async:          {:>8.2} ms  ({:>7.0} req/s)",
+        awaited.as_secs_f64() * 1e3,
+        per_sec(awaited)
+    );
+}
@@ -0,0 +1,29 @@
+-- Full-text index over item title/description.
+-- External-content FTS5 table: text lives in `items`, the index in `items_fts`.
+CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
+    title,
+    description,
+    content = 'items',
+    content_rowid = 'id'
+);
+
+-- Index rows that already exist (seed data, pre-migration databases)
+INSERT INTO items_fts (items_fts) VALUES ('rebuild');
+
+-- Keep the index in sync with every write to `items`
+CREATE TRIGGER IF NOT EXISTS items_fts_after_insert AFTER INSERT ON items BEGIN
+    INSERT INTO items_fts (rowid, title, description)
+    VALUES (new.id, new.title, new.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS items_fts_after_delete AFTER DELETE ON items BEGIN
+    INSERT INTO items_fts (items_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+END;
+
+CREATE TRIGGER IF NOT EXISTS items_fts_after_update AFTER UPDATE OF title, description ON items BEGIN
+    INSERT INTO items_fts (items_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+    INSERT INTO items_fts (rowid, title, description)
+    VALUES (new.id, new.title, new.description);
+END;
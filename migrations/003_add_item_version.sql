@@ -0,0 +1,3 @@
+-- Optimistic concurrency: every write bumps `version`, and edits only apply
+-- when the version they were based on is still current.
+ALTER TABLE items ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
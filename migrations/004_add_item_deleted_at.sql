@@ -0,0 +1,5 @@
+-- Soft delete: trashed items keep their row until purged.
+-- NULL = live item; otherwise the time it was moved to the trash.
+ALTER TABLE items ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_items_deleted_at ON items (deleted_at);
@@ -0,0 +1,11 @@
+-- Creation and last-write times, set by the service layer on every write.
+-- SQLite can't add columns with a non-constant default, so existing rows are
+-- backfilled with the migration time (RFC 3339, like the values sqlx writes).
+ALTER TABLE items ADD COLUMN created_at TEXT;
+ALTER TABLE items ADD COLUMN updated_at TEXT;
+UPDATE items SET
+    created_at = strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now'),
+    updated_at = strftime('%Y-%m-%dT%H:%M:%S+00:00', 'now')
+WHERE created_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_items_created_at ON items (created_at, id);
+CREATE INDEX IF NOT EXISTS idx_items_updated_at ON items (updated_at, id);
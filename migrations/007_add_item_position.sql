@@ -0,0 +1,5 @@
+-- Manual ordering: items are listed by `position`, renumbered by reorders.
+-- Existing items keep their creation order.
+ALTER TABLE items ADD COLUMN position INTEGER NOT NULL DEFAULT 0;
+UPDATE items SET position = id;
+CREATE INDEX IF NOT EXISTS idx_items_position ON items (position, id);
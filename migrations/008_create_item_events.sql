@@ -0,0 +1,14 @@
+-- Audit trail: one row per change to an item. old_values/new_values are JSON
+-- objects holding only the fields the change touched. History goes with the
+-- item when it is purged.
+CREATE TABLE IF NOT EXISTS item_events (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id    INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+    kind       TEXT NOT NULL,
+    actor      TEXT NOT NULL,
+    old_values TEXT NOT NULL DEFAULT '{}',
+    new_values TEXT NOT NULL DEFAULT '{}',
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_events_item_id ON item_events (item_id, id);
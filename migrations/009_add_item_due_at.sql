@@ -0,0 +1,5 @@
+-- Optional deadline per item. reminded_due_at is the due_at the reminder job
+-- last notified about, so moving the deadline re-arms the reminder.
+ALTER TABLE items ADD COLUMN due_at TEXT;
+ALTER TABLE items ADD COLUMN reminded_due_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_items_due_at ON items (due_at) WHERE due_at IS NOT NULL;
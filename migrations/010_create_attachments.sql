@@ -0,0 +1,14 @@
+-- Files attached to items. Only metadata lives here; the bytes are in blob
+-- storage under storage_key. Rows go with the item when it is purged (the
+-- service removes the blobs).
+CREATE TABLE IF NOT EXISTS attachments (
+    id           INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id      INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+    file_name    TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    size         INTEGER NOT NULL,
+    storage_key  TEXT NOT NULL UNIQUE,
+    created_at   TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_item_id ON attachments (item_id, id);
@@ -0,0 +1,5 @@
+-- Subtasks: an item may belong to a parent item. Subtasks are purged with
+-- their parent; trashing and restoring is cascaded by the service layer.
+ALTER TABLE items ADD COLUMN parent_id INTEGER REFERENCES items (id) ON DELETE CASCADE;
+
+CREATE INDEX IF NOT EXISTS idx_items_parent_id ON items (parent_id);
@@ -0,0 +1,12 @@
+-- Discussion on an item, oldest first. `author` is the actor label of the
+-- session that wrote it (see item_events). Comments go with their item when
+-- it is purged.
+CREATE TABLE IF NOT EXISTS item_comments (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id    INTEGER NOT NULL REFERENCES items (id) ON DELETE CASCADE,
+    author     TEXT NOT NULL,
+    body       TEXT NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_comments_item_id ON item_comments (item_id, id);
@@ -0,0 +1,20 @@
+-- Server-side sessions (SqliteSessionStore), keyed by a SHA-256 hash of the
+-- session id — the cookie value itself is never stored. `data` is a JSON
+-- object; expired rows are deleted by expires_at.
+CREATE TABLE IF NOT EXISTS sessions (
+    id_hash     TEXT PRIMARY KEY,
+    csrf_token  TEXT NOT NULL DEFAULT '',
+    data        TEXT NOT NULL DEFAULT '{}',
+    created_at  TEXT NOT NULL,
+    last_access TEXT NOT NULL,
+    expires_at  TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at ON sessions (expires_at);
+
+-- Server secrets that must outlive a restart, e.g. the CSRF signing key
+-- (tokens signed with a fresh key would reject every persisted session)
+CREATE TABLE IF NOT EXISTS app_secrets (
+    name  TEXT PRIMARY KEY,
+    value BLOB NOT NULL
+);
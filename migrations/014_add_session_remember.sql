@@ -0,0 +1,3 @@
+-- Remember-me: the session opted into the longer `[session] remember_me_days`
+-- lifetime. `expires_at` is recomputed by the store whenever this changes.
+ALTER TABLE sessions ADD COLUMN remember INTEGER NOT NULL DEFAULT 0;
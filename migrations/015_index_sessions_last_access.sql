@@ -0,0 +1,2 @@
+-- The session cap evicts the least recently used sessions first
+CREATE INDEX IF NOT EXISTS idx_sessions_last_access ON sessions (last_access);
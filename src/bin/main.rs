@@ -1,7 +1,11 @@
 use std::sync::Arc;
 use std::time::SystemTime;
 
-use axum::{middleware, routing::get, Router};
+use axum::{
+    middleware,
+    routing::{delete, get, patch, post},
+    Router,
+};
 use tower::ServiceBuilder;
 use tower_http::{services::ServeDir, trace::TraceLayer};
 use tracing::info;
@@ -9,10 +13,15 @@ use tracing::info;
 use app::{
     config::AppConfig,
     db,
-    handlers::{partials, templates},
+    handlers::{attachments, comments, partials, templates, transfer},
     middleware as mw,
     models::AppState,
-    services::Services,
+    services::{
+        reminders::{FileOutboxNotifier, LogNotifier, Notifier, ReminderJob},
+        session,
+        storage::LocalDiskStorage,
+        Services,
+    },
     utils::logging,
 };
 
@@ -34,12 +43,41 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .await
         .expect("Failed to initialize database");
 
+    // Attachment files live on local disk under the data directory
+    let storage = Arc::new(LocalDiskStorage::new(
+        std::path::Path::new(&config.storage.dir).join("attachments"),
+    ));
+
     // Initialize services (includes CSRF secret + session store)
-    let services = Services::new_with_db(SystemTime::now(), db.clone());
+    let services = Services::new_with_db(SystemTime::now(), db.clone(), storage, &config.session)
+        .await
+        .expect("Failed to initialize services");
 
     // Shared state with services
     let state = Arc::new(AppState::new(services, db));
 
+    // Expired sessions are deleted in the background
+    session::spawn_reaper(
+        state.services.sessions.clone(),
+        std::time::Duration::from_secs(config.session.cleanup_interval_secs.max(1)),
+    );
+
+    // Reminders for items coming due
+    if config.reminders.enabled {
+        let notifier: Arc<dyn Notifier> = match &config.reminders.outbox {
+            Some(path) => Arc::new(FileOutboxNotifier::new(path)),
+            None => Arc::new(LogNotifier),
+        };
+        ReminderJob::new(
+            state.services.items.clone(),
+            notifier,
+            chrono::Duration::minutes(config.reminders.lead_minutes),
+        )
+        .spawn(std::time::Duration::from_secs(
+            config.reminders.interval_secs.max(1),
+        ));
+    }
+
     // ── Routes ──────────────────────────────────────────────────────────
     // No JSON API. No Swagger. No CORS.
     // Every route returns HTML — full pages or HTMX partials.
@@ -48,7 +86,50 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let partial_routes = Router::new()
         .route("/partials/status-card", get(partials::status_card))
         .route("/partials/item-list", get(partials::item_list))
-        .route("/partials/greeting", get(partials::greeting));
+        .route("/partials/item-search", get(partials::item_search))
+        .route("/partials/item-trash", get(partials::item_trash))
+        .route("/partials/tags", get(partials::tag_list))
+        .route("/partials/tags/:name", delete(partials::delete_tag))
+        .route("/partials/greeting", get(partials::greeting))
+        // Item writes (CSRF-protected — state-changing methods only)
+        .route("/partials/items", post(partials::create_item))
+        .route("/partials/items/reorder", post(partials::reorder_items))
+        .route(
+            "/partials/items/import/preview",
+            post(transfer::import_preview),
+        )
+        .route("/partials/items/import", post(transfer::import_commit))
+        .route("/partials/items/bulk/:action", post(partials::bulk_items))
+        .route("/partials/items/:id/toggle", patch(partials::toggle_item))
+        .route("/partials/items/:id/edit", get(partials::edit_item))
+        .route("/partials/items/:id/history", get(partials::item_history))
+        .route(
+            "/partials/items/:id/children",
+            get(partials::item_children).post(partials::create_child),
+        )
+        .route(
+            "/partials/items/:id/attachments",
+            get(attachments::item_attachments)
+                .post(attachments::upload_attachment)
+                .layer(attachments::upload_limit()),
+        )
+        .route(
+            "/partials/attachments/:id",
+            delete(attachments::delete_attachment),
+        )
+        .route(
+            "/partials/items/:id/comments",
+            get(comments::item_comments).post(comments::add_comment),
+        )
+        .route("/partials/comments/:id", delete(comments::delete_comment))
+        .route("/partials/items/:id/restore", post(partials::restore_item))
+        .route("/partials/items/:id/purge", delete(partials::purge_item))
+        .route(
+            "/partials/items/:id",
+            get(partials::item_row)
+                .put(partials::update_item)
+                .delete(partials::delete_item),
+        );
 
     // Health check (no middleware — used by Docker HEALTHCHECK)
     let health_route = Router::new().route("/healthz", get(app::handlers::healthz));
@@ -58,17 +139,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/", get(templates::home_page))
         .route("/about", get(templates::about_page))
         .route("/demo", get(templates::demo_page))
+        .route("/items", get(templates::items_page))
+        .route("/items/export/:format", get(transfer::export_items))
+        .route("/attachments/:id", get(attachments::download_attachment))
         .merge(partial_routes)
         .merge(health_route)
         // Static files (vendored CSS, JS, fonts — no external CDN)
         .nest_service("/static", ServeDir::new("static"))
-        // Inject shared state into extensions for middleware access
-        .layer(axum::Extension(state.clone()))
         .with_state(state.clone())
         // ── Middleware (applied bottom-up) ───────────────────────────────
         .layer(
             ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
+                // Inject shared state into extensions for middleware access —
+                // must wrap the session/CSRF layers below so they can see it
+                .layer(axum::Extension(state.clone()))
                 .layer(middleware::from_fn(mw::request_logger))
                 .layer(middleware::from_fn(mw::security_headers))
                 .layer(middleware::from_fn(mw::session_middleware))
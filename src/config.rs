@@ -1,5 +1,8 @@
+use chrono::Duration;
 use serde::{Deserialize, Serialize};
 
+use crate::services::session::SessionLifetime;
+
 /// Application configuration loaded from config/app.toml + env vars
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AppConfig {
@@ -7,6 +10,12 @@ pub struct AppConfig {
     pub logging: LoggingConfig,
     pub environment: EnvironmentConfig,
     pub database: DatabaseConfig,
+    #[serde(default)]
+    pub reminders: RemindersConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub session: SessionConfig,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -31,6 +40,102 @@ pub struct DatabaseConfig {
     pub url: String,
 }
 
+/// Background job that notifies about items coming due
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RemindersConfig {
+    pub enabled: bool,
+    /// Seconds between scans
+    pub interval_secs: u64,
+    /// Remind this many minutes before an item is due
+    pub lead_minutes: i64,
+    /// Append reminders as JSON lines to this file instead of logging them
+    pub outbox: Option<String>,
+}
+
+impl Default for RemindersConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 60,
+            lead_minutes: 60,
+            outbox: None,
+        }
+    }
+}
+
+/// Where uploaded files are kept
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// Data directory; attachments go in its `attachments/` subdirectory
+    pub dir: String,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            dir: "data".to_string(),
+        }
+    }
+}
+
+/// Server-side session settings
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub store: SessionStoreKind,
+    /// Minutes without a request before a session expires
+    pub idle_timeout_minutes: i64,
+    /// Hours after its creation when a session expires, however active
+    pub max_lifetime_hours: i64,
+    /// Days a session that opted into remember-me is kept; unset disables
+    /// remember-me
+    pub remember_me_days: Option<i64>,
+    /// Most sessions kept at once; past it the least recently used are
+    /// evicted. 0 for no limit.
+    pub max_sessions: usize,
+    /// Seconds between sweeps deleting expired sessions
+    pub cleanup_interval_secs: u64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            store: SessionStoreKind::default(),
+            idle_timeout_minutes: 60,
+            max_lifetime_hours: 24,
+            remember_me_days: None,
+            max_sessions: 10_000,
+            cleanup_interval_secs: 300,
+        }
+    }
+}
+
+impl SessionConfig {
+    pub fn lifetime(&self) -> SessionLifetime {
+        SessionLifetime {
+            idle: Duration::minutes(self.idle_timeout_minutes),
+            absolute: Duration::hours(self.max_lifetime_hours),
+            remember: self.remember_me_days.map(Duration::days),
+        }
+    }
+
+    pub fn max_sessions(&self) -> Option<usize> {
+        (self.max_sessions > 0).then_some(self.max_sessions)
+    }
+}
+
+/// Where sessions live: `sqlite` (the database, survives restarts) or
+/// `memory` (lost on every restart, logging everyone out)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStoreKind {
+    Memory,
+    #[default]
+    Sqlite,
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -48,6 +153,9 @@ impl Default for AppConfig {
             database: DatabaseConfig {
                 url: "sqlite://data.db?mode=rwc".to_string(),
             },
+            reminders: RemindersConfig::default(),
+            storage: StorageConfig::default(),
+            session: SessionConfig::default(),
         }
     }
 }
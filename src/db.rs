@@ -3,7 +3,8 @@
 //! Uses SQLx with SQLite. The pool is created once at startup and shared
 //! across all handlers via AppState.
 
-use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use sqlx::sqlite::{Sqlite, SqlitePool, SqlitePoolOptions};
+use sqlx::Transaction;
 use tracing::info;
 
 /// Type alias for the database connection pool
@@ -13,7 +14,7 @@ pub type Db = SqlitePool;
 ///
 /// The `database_url` should be a SQLite connection string, e.g.:
 /// - `sqlite://data.db?mode=rwc` (file-based, auto-create)
-/// - `sqlite::memory:` (in-memory, useful for tests)
+/// - `sqlite::memory:` (in-memory — prefer [`init_memory_pool`], see below)
 pub async fn init_pool(database_url: &str) -> Result<Db, sqlx::Error> {
     info!("Connecting to database: {}", database_url);
 
@@ -29,3 +30,28 @@ pub async fn init_pool(database_url: &str) -> Result<Db, sqlx::Error> {
 
     Ok(pool)
 }
+
+/// Single-connection in-memory pool with migrations applied.
+///
+/// Every SQLite `:memory:` connection is its own database, so the pool is
+/// capped at one connection to keep the schema and data visible to all queries.
+pub async fn init_memory_pool() -> Result<Db, sqlx::Error> {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await?;
+
+    sqlx::migrate!("./migrations").run(&pool).await?;
+
+    Ok(pool)
+}
+
+/// Begin a transaction that takes SQLite's write lock up front.
+///
+/// A plain `BEGIN` is deferred: two connections can both read, then fail
+/// with `database is locked` when each tries to upgrade to a writer.
+/// `BEGIN IMMEDIATE` makes the second writer wait out the busy timeout
+/// instead, so concurrent writes queue rather than error.
+pub async fn begin_write(pool: &Db) -> Result<Transaction<'static, Sqlite>, sqlx::Error> {
+    pool.begin_with("BEGIN IMMEDIATE").await
+}
@@ -7,10 +7,57 @@ use axum::{
     http::{header, StatusCode},
     response::{Html, IntoResponse, Response},
 };
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
 use thiserror::Error;
 
 pub type AppResult<T> = std::result::Result<T, AppError>;
 
+/// Per-field validation messages, keyed by form field name.
+///
+/// Templates read them as `errors["title"]` — works in both askama (via
+/// `Index`) and minijinja (map lookup); unknown fields yield no messages.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FieldErrors(BTreeMap<String, Vec<String>>);
+
+impl FieldErrors {
+    pub fn add(&mut self, field: &str, message: impl Into<String>) {
+        self.0
+            .entry(field.to_string())
+            .or_default()
+            .push(message.into());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Every message, in field order
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.0.values().flatten().map(String::as_str)
+    }
+}
+
+impl std::ops::Index<&str> for FieldErrors {
+    type Output = [String];
+
+    fn index(&self, field: &str) -> &[String] {
+        self.0.get(field).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+impl fmt::Display for FieldErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<String> = self
+            .0
+            .iter()
+            .map(|(field, msgs)| format!("{}: {}", field, msgs.join(", ")))
+            .collect();
+        f.write_str(&messages.join("; "))
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Not found: {0}")]
@@ -22,9 +69,24 @@ pub enum AppError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
     #[error("Validation failed: {0}")]
     Validation(String),
 
+    /// Form-level validation failure: `form` is the submitted form re-rendered
+    /// with inline errors, swapped over `target` instead of the error toast
+    #[error("Validation failed: {errors}")]
+    InvalidForm {
+        errors: FieldErrors,
+        target: String,
+        form: String,
+    },
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 
@@ -41,7 +103,10 @@ impl AppError {
         match self {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
             AppError::BadRequest(_) | AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::InvalidForm { .. } => StatusCode::UNPROCESSABLE_ENTITY,
             AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
             AppError::Internal(_) | AppError::Anyhow(_) | AppError::Database(_) => {
                 StatusCode::INTERNAL_SERVER_ERROR
             }
@@ -53,7 +118,9 @@ impl AppError {
         match self {
             AppError::NotFound(_) => "warning",
             AppError::BadRequest(_) | AppError::Validation(_) => "warning",
-            AppError::Unauthorized => "danger",
+            AppError::InvalidForm { .. } => "warning",
+            AppError::Conflict(_) => "warning",
+            AppError::Unauthorized | AppError::Forbidden(_) => "danger",
             _ => "danger",
         }
     }
@@ -63,7 +130,9 @@ impl AppError {
         match self {
             AppError::NotFound(_) => "search",
             AppError::BadRequest(_) | AppError::Validation(_) => "exclamation-triangle",
-            AppError::Unauthorized => "lock",
+            AppError::InvalidForm { .. } => "exclamation-triangle",
+            AppError::Conflict(_) => "arrow-repeat",
+            AppError::Unauthorized | AppError::Forbidden(_) => "lock",
             _ => "x-circle",
         }
     }
@@ -72,9 +141,26 @@ impl AppError {
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let status = self.status_code();
+
+        // Form errors replace the form itself, not the toast
+        if let AppError::InvalidForm { target, form, .. } = self {
+            let mut response = (status, Html(form)).into_response();
+            if let Ok(target) = target.parse() {
+                response
+                    .headers_mut()
+                    .insert(header::HeaderName::from_static("hx-retarget"), target);
+            }
+            response.headers_mut().insert(
+                header::HeaderName::from_static("hx-reswap"),
+                "outerHTML".parse().unwrap(),
+            );
+            return response;
+        }
+
         let alert_class = self.alert_class();
         let icon = self.icon();
-        let message = self.to_string();
+        // Messages can echo user input (tag names, import rows)
+        let message = html_escape::encode_text(&self.to_string()).into_owned();
 
         // Render as HTML fragment for HTMX
         let body = format!(
@@ -105,6 +191,20 @@ impl IntoResponse for AppError {
     }
 }
 
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        match err {
+            sqlx::Error::RowNotFound => AppError::NotFound("Record".to_string()),
+            other => {
+                // The details (SQL, constraint names, file paths) go to the
+                // log only; the client gets a generic message
+                tracing::error!(error = %other, "database error");
+                AppError::Database("the request could not be completed".to_string())
+            }
+        }
+    }
+}
+
 // Convenience constructors
 impl AppError {
     pub fn not_found(msg: impl Into<String>) -> Self {
@@ -119,7 +219,29 @@ impl AppError {
         Self::Internal(msg.into())
     }
 
+    pub fn forbidden(msg: impl Into<String>) -> Self {
+        Self::Forbidden(msg.into())
+    }
+
     pub fn validation(msg: impl Into<String>) -> Self {
         Self::Validation(msg.into())
     }
+
+    /// Re-render a form in place: `target` is the CSS selector of the form
+    /// element and `form` its HTML with inline errors and the submitted values
+    pub fn invalid_form(
+        errors: FieldErrors,
+        target: impl Into<String>,
+        form: Html<String>,
+    ) -> Self {
+        Self::InvalidForm {
+            errors,
+            target: target.into(),
+            form: form.0,
+        }
+    }
+
+    pub fn conflict(msg: impl Into<String>) -> Self {
+        Self::Conflict(msg.into())
+    }
 }
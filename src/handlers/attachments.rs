@@ -0,0 +1,197 @@
+//! Attachment Handlers — upload, list, download and delete item files
+//!
+//! The list and upload form are an HTMX partial inside each item row.
+//! Downloads are always served as `attachment` with the sniffed content type
+//! and `nosniff`, so an uploaded file is never rendered as part of the app.
+
+use axum::{
+    extract::{multipart::MultipartError, DefaultBodyLimit, Multipart, Path, State},
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::error::{AppError, AppResult};
+use crate::models::AppState;
+use crate::services::attachments::{file_too_large, Attachment, MAX_ATTACHMENT_BYTES};
+
+// Askama looks up custom filters (e.g. `filesize`) as `filters::*` in this module
+#[cfg(not(debug_assertions))]
+use crate::utils::filters;
+
+crate::define_partial!(AttachmentListPartial, "partials/attachment_list.html", {
+    item_id: u32,
+    attachments: Vec<Attachment>
+});
+
+/// Body limit for the upload route — the largest file plus room for the
+/// multipart framing
+pub fn upload_limit() -> DefaultBodyLimit {
+    DefaultBodyLimit::max(MAX_ATTACHMENT_BYTES + 64 * 1024)
+}
+
+async fn list_partial(state: &AppState, item_id: u32) -> AppResult<Html<String>> {
+    let attachments = state.services.attachments.list(item_id).await?;
+    Ok(AttachmentListPartial {
+        item_id,
+        attachments,
+    }
+    .render_response())
+}
+
+/// Attachment list — loaded when the row's "Attachments" disclosure opens
+pub async fn item_attachments(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.get_by_id(id).await?;
+    list_partial(&state, id).await
+}
+
+/// Upload — attach the posted `file` to a live item and return the
+/// refreshed list
+pub async fn upload_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    mut multipart: Multipart,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.get_by_id(id).await?;
+
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::bad_request(e.body_text()))?
+    {
+        if field.name() == Some("file") {
+            let file_name = field.file_name().unwrap_or("file").to_string();
+            let bytes = field.bytes().await.map_err(upload_error)?;
+            upload = Some((file_name, bytes));
+        }
+    }
+    let (file_name, bytes) =
+        upload.ok_or_else(|| AppError::validation("Choose a file to attach"))?;
+
+    state
+        .services
+        .attachments
+        .add(id, &file_name, bytes.to_vec())
+        .await?;
+    list_partial(&state, id).await
+}
+
+/// Oversized bodies are cut off by the route's body limit mid-stream
+fn upload_error(err: MultipartError) -> AppError {
+    if err.status() == StatusCode::PAYLOAD_TOO_LARGE {
+        file_too_large()
+    } else {
+        AppError::bad_request(err.body_text())
+    }
+}
+
+/// Delete attachment — returns the refreshed list of its item
+pub async fn delete_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    let attachment = state.services.attachments.get(id).await?;
+    // Files of trashed items stay as they are until restore or purge
+    state.services.items.get_by_id(attachment.item_id).await?;
+    state.services.attachments.delete(id).await?;
+    list_partial(&state, attachment.item_id).await
+}
+
+/// Download — the stored bytes, always as a file download. Files of
+/// trashed items are hidden along with the item.
+pub async fn download_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<Response> {
+    let attachment = state.services.attachments.get(id).await?;
+    state.services.items.get_by_id(attachment.item_id).await?;
+    let (attachment, bytes) = state.services.attachments.read(id).await?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, attachment.content_type.clone()),
+            (
+                header::CONTENT_DISPOSITION,
+                content_disposition(&attachment.file_name),
+            ),
+            (header::X_CONTENT_TYPE_OPTIONS, "nosniff".to_string()),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// `attachment` disposition with an ASCII fallback name and the exact name
+/// percent-encoded per RFC 6266 / 8187
+fn content_disposition(file_name: &str) -> String {
+    let fallback: String = file_name
+        .chars()
+        .map(|c| match c {
+            ' '..='~' if c != '"' && c != '\\' => c,
+            _ => '_',
+        })
+        .collect();
+    let mut encoded = String::new();
+    for byte in file_name.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'.' | b'-' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    format!(
+        "attachment; filename=\"{}\"; filename*=UTF-8''{}",
+        fallback, encoded
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+    use axum::{body::Body, http::Request, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_download_hides_files_of_trashed_items() {
+        let state = test_state().await;
+        let attachment = state
+            .services
+            .attachments
+            .add(2, "notes.txt", b"plain text".to_vec())
+            .await
+            .unwrap();
+        let app = Router::new()
+            .route("/attachments/:id", get(download_attachment))
+            .with_state(state.clone());
+        let download = || {
+            let request = Request::get(format!("/attachments/{}", attachment.id))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        assert_eq!(download().await.unwrap().status(), StatusCode::OK);
+        state.services.items.delete(2, "tester").await.unwrap();
+        assert_eq!(download().await.unwrap().status(), StatusCode::NOT_FOUND);
+        state.services.items.restore(2, "tester").await.unwrap();
+        assert_eq!(download().await.unwrap().status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_content_disposition_quotes_safely() {
+        assert_eq!(
+            content_disposition("report.pdf"),
+            "attachment; filename=\"report.pdf\"; filename*=UTF-8''report.pdf"
+        );
+        assert_eq!(
+            content_disposition("a\"b\\ ü.txt"),
+            "attachment; filename=\"a_b_ _.txt\"; filename*=UTF-8''a%22b%5C%20%C3%BC.txt"
+        );
+    }
+}
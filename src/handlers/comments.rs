@@ -0,0 +1,127 @@
+//! Comment Handlers — the discussion thread under each item
+//!
+//! The thread is an HTMX partial loaded when the row's "Comments" disclosure
+//! first opens. A new comment comes back as a single entry appended to the
+//! thread (`hx-swap="beforeend"`); deleting one swaps its entry for nothing.
+//! Only a comment's author can delete it.
+
+use axum::{
+    extract::{Path, State},
+    response::{Html, IntoResponse},
+    Form,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::Actor;
+use crate::error::{AppError, AppResult};
+use crate::models::AppState;
+use crate::services::comments::Comment;
+
+// Askama looks up custom filters (e.g. `md`) as `filters::*` in this module
+#[cfg(not(debug_assertions))]
+use crate::utils::filters;
+
+crate::define_partial!(CommentListPartial, "partials/comment_list.html", {
+    item_id: u32,
+    comments: Vec<Comment>,
+    actor: String
+});
+
+crate::define_partial!(CommentPartial, "partials/comment.html", {
+    comment: Comment,
+    actor: String
+});
+
+/// Comment thread of a live item, oldest first, with the "add" form
+pub async fn item_comments(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.get_by_id(id).await?;
+    let comments = state.services.comments.list(id).await?;
+
+    Ok(CommentListPartial {
+        item_id: id,
+        comments,
+        actor,
+    }
+    .render_response())
+}
+
+/// Post a comment — returns just the new entry, for appending to the thread
+pub async fn add_comment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+    Form(form): Form<CommentForm>,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.get_by_id(id).await?;
+    let comment = state.services.comments.add(id, &actor, &form.body).await?;
+
+    Ok(CommentPartial { comment, actor }.render_response())
+}
+
+/// Delete comment — an empty body, so the entry swaps itself away
+pub async fn delete_comment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+) -> AppResult<impl IntoResponse> {
+    let comment = state.services.comments.get(id).await?;
+    // Threads of trashed items stay as they are until restore or purge
+    state.services.items.get_by_id(comment.item_id).await?;
+    if comment.author != actor {
+        return Err(AppError::forbidden("Only its author can delete a comment"));
+    }
+    state.services.comments.delete(id).await?;
+
+    Ok(Html(String::new()))
+}
+
+#[derive(Deserialize)]
+pub struct CommentForm {
+    #[serde(default)]
+    pub body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+    use axum::{body::Body, http::Request, http::StatusCode, routing::delete, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_only_the_author_deletes_a_comment() {
+        let state = test_state().await;
+        let comments = &state.services.comments;
+        let theirs = comments.add(1, "session a", "Mine").await.unwrap();
+        // Requests without a session act as "anonymous"
+        let ours = comments.add(1, "anonymous", "Ours").await.unwrap();
+        let app = Router::new()
+            .route("/partials/comments/:id", delete(delete_comment))
+            .with_state(state.clone());
+        let delete = |id: u32| {
+            let request = Request::delete(format!("/partials/comments/{}", id))
+                .body(Body::empty())
+                .unwrap();
+            app.clone().oneshot(request)
+        };
+
+        assert_eq!(
+            delete(theirs.id).await.unwrap().status(),
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(delete(ours.id).await.unwrap().status(), StatusCode::OK);
+        let left: Vec<u32> = comments
+            .list(1)
+            .await
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(left, [theirs.id]);
+    }
+}
@@ -1,5 +1,31 @@
+use axum::{async_trait, extract::FromRequestParts, http::request::Parts};
+use std::convert::Infallible;
+
+use crate::services::session::CurrentSession;
+
+pub mod attachments;
+pub mod comments;
 pub mod partials;
 pub mod templates;
+pub mod transfer;
+
+/// Who is making a request, as recorded in item history — derived from the
+/// request's session, which the CSRF layer has already checked on every write
+pub struct Actor(pub String);
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for Actor {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let actor = parts
+            .extensions
+            .get::<CurrentSession>()
+            .map(CurrentSession::actor)
+            .unwrap_or_else(|| "anonymous".to_string());
+        Ok(Actor(actor))
+    }
+}
 
 /// Lightweight health check — no auth, no session, no template rendering
 pub async fn healthz() -> &'static str {
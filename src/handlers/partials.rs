@@ -4,14 +4,29 @@
 //! HTMX swaps them into the existing page for SPA-like interactivity.
 
 use axum::{
-    extract::{Query, State},
-    response::{Html, IntoResponse},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Response},
+    Form,
 };
-use serde::Deserialize;
+use axum_extra::extract::Form as HtmlForm;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+use super::Actor;
+use crate::error::{AppError, AppResult, FieldErrors};
 use crate::models::AppState;
-use crate::services::items::Item;
+use crate::services::items::{
+    empty_as_none, parse_due, parse_parent, parse_tags, Item, ItemChanges, ItemEvent, ItemQuery,
+    ItemSort, StatusFilter, Tag,
+};
+use crate::services::session::{CurrentSession, FlashLevel};
+use crate::utils::highlight::{highlight, search_terms, Segment};
+
+// Askama looks up custom filters (e.g. `timeago`) as `filters::*` in this module
+#[cfg(not(debug_assertions))]
+use crate::utils::filters;
 
 // =============================================================================
 // Partial Templates — using the macro for dual-mode rendering
@@ -23,17 +38,97 @@ crate::define_partial!(StatusCardPartial, "partials/status_card.html", {
     version: String
 });
 
+// `sortable` enables drag-and-drop reordering (manual order only)
 crate::define_partial!(ItemListPartial, "partials/item_list.html", {
+    items: Vec<Item>,
+    next_url: String,
+    sortable: bool
+});
+
+// Follow-up pages of the item list — rows plus the next "load more" sentinel
+crate::define_partial!(ItemPagePartial, "partials/item_page.html", {
+    items: Vec<Item>,
+    next_url: String
+});
+
+crate::define_partial!(ItemRowPartial, "partials/item_row.html", {
+    item: Item
+});
+
+// Subtasks of one item plus the "Add subtask" form, inside the parent's row
+crate::define_partial!(ItemChildrenPartial, "partials/item_children.html", {
+    parent: Item,
     items: Vec<Item>
 });
 
+// "3/5 done" — refreshed out-of-band when a subtask changes
+crate::define_partial!(ItemProgressPartial, "partials/item_progress.html", {
+    item: Item
+});
+
+crate::define_partial!(ItemFormPartial, "partials/item_form.html", {
+    form: ItemForm
+});
+
+crate::define_partial!(ItemEditPartial, "partials/item_edit.html", {
+    item: Item,
+    form: ItemForm
+});
+
+// Shown in place of the edit form when the item changed underneath it
+crate::define_partial!(ItemConflictPartial, "partials/item_conflict.html", {
+    item: Item,
+    form: ItemForm
+});
+
+// Left in place of a trashed row, offering "Undo" until it dismisses itself
+crate::define_partial!(ItemDeletedPartial, "partials/item_deleted.html", {
+    item: Item,
+    grace_ms: u64
+});
+
+crate::define_partial!(ItemHistoryPartial, "partials/item_history.html", {
+    events: Vec<ItemEvent>
+});
+
+crate::define_partial!(ItemTrashPartial, "partials/item_trash.html", {
+    entries: Vec<TrashEntry>
+});
+
+// "7 items moved to the trash" — swapped out-of-band next to the bulk toolbar
+crate::define_partial!(BulkSummaryPartial, "partials/bulk_summary.html", {
+    message: String
+});
+
+crate::define_partial!(TagListPartial, "partials/tag_list.html", {
+    tags: Vec<Tag>
+});
+
+crate::define_partial!(ItemSearchPartial, "partials/item_search.html", {
+    query: String,
+    hits: Vec<SearchHit>
+});
+
+/// Items per page in the infinite-scroll item list
+const ITEM_PAGE_SIZE: u32 = 20;
+
+/// Maximum number of active-search results
+const SEARCH_LIMIT: u32 = 20;
+
+/// How long the "Undo" affordance stays up after a delete
+const UNDO_GRACE_MS: u64 = 10_000;
+
+/// Field limits shared by the create and edit forms (mirrored as `maxlength`)
+const TITLE_MAX_CHARS: usize = 200;
+const DESCRIPTION_MAX_CHARS: usize = 1000;
+
 // =============================================================================
 // Partial Handlers
 // =============================================================================
 
 /// Status card partial — shows server health on the dashboard
 pub async fn status_card(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let health = state.services.health.get_status();
+    let health = state.services.health.get_status().await;
 
     StatusCardPartial {
         status: health.status,
@@ -43,10 +138,493 @@ pub async fn status_card(State(state): State<Arc<AppState>>) -> impl IntoRespons
     .render_response()
 }
 
-/// Item list partial — returns a list of items as an HTML fragment
-pub async fn item_list(State(state): State<Arc<AppState>>) -> impl IntoResponse {
-    let items = state.services.items.list_all();
-    ItemListPartial { items }.render_response()
+/// Fetch one page of top-level items plus the URL of the next page ("" on
+/// the last page); subtasks are loaded under their parent row
+async fn item_page(
+    state: &AppState,
+    query: &ItemQuery,
+    after: Option<u32>,
+) -> AppResult<(Vec<Item>, String)> {
+    let top_level = ItemQuery {
+        top_level: true,
+        ..query.clone()
+    };
+    let page = state
+        .services
+        .items
+        .list_page(&top_level, after, ITEM_PAGE_SIZE)
+        .await?;
+    let next_url = page
+        .next_cursor
+        .map(|cursor| {
+            format!(
+                "/partials/item-list?{}&after={}",
+                query.to_query_string(),
+                cursor
+            )
+        })
+        .unwrap_or_default();
+    Ok((page.items, next_url))
+}
+
+/// Item list partial — returns the first page of items as an HTML fragment,
+/// filtered and sorted by `?status=` and `?sort=`. With `?after=<id>` it
+/// returns only the following page, for the `hx-trigger="revealed"` sentinel
+/// at the bottom of the list.
+///
+/// When the items page shows a first page whose filter differs from its URL,
+/// the URL is updated so the filter survives reloads and back/forward:
+/// `HX-Push-Url` when the user changed the filter (`?push=true`), otherwise
+/// `HX-Replace-Url` — the initial load must not add a history entry, or Back
+/// would land on the same page again.
+pub async fn item_list(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ItemListQuery>,
+    Query(query): Query<ItemQuery>,
+    headers: HeaderMap,
+) -> AppResult<Response> {
+    let (items, next_url) = item_page(&state, &query, params.after).await?;
+
+    if params.after.is_some() {
+        return Ok(ItemPagePartial { items, next_url }
+            .render_response()
+            .into_response());
+    }
+
+    let list = ItemListPartial {
+        items,
+        next_url,
+        sortable: query.sort == ItemSort::Manual,
+    }
+    .render_response();
+    let page_url = format!("/items?{}", query.to_query_string());
+    let header = match current_url_path(&headers) {
+        Some(current) if current == page_url => None,
+        _ if params.push => Some("HX-Push-Url"),
+        Some(current) if current.split('?').next() == Some("/items") => Some("HX-Replace-Url"),
+        // Lists embedded in other pages leave their URL alone
+        _ => None,
+    };
+    match header {
+        Some(name) => Ok(([(name, page_url)], list).into_response()),
+        None => Ok(list.into_response()),
+    }
+}
+
+/// Path and query of the page that issued an HTMX request (`HX-Current-URL`)
+fn current_url_path(headers: &HeaderMap) -> Option<&str> {
+    let url = headers.get("hx-current-url")?.to_str().ok()?;
+    let after_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    after_scheme.find('/').map(|i| &after_scheme[i..])
+}
+
+/// Create item — returns a blank form and fires `items-changed`, which
+/// reloads the list with its current filter. Invalid input re-renders the
+/// form in place with a 422.
+pub async fn create_item(
+    State(state): State<Arc<AppState>>,
+    Actor(actor): Actor,
+    Form(mut form): Form<ItemForm>,
+) -> AppResult<impl IntoResponse> {
+    if let Err(errors) = form.validate() {
+        form.errors = errors.clone();
+        let html = ItemFormPartial { form }.render_response();
+        return Err(AppError::invalid_form(errors, "#item-create-form", html));
+    }
+
+    state.services.items.create(form.changes(), &actor).await?;
+
+    let blank = ItemFormPartial {
+        form: ItemForm::default(),
+    };
+    Ok(([("HX-Trigger", "items-changed")], blank.render_response()))
+}
+
+/// Item row — the read-only view of a single item (also "cancel edit")
+pub async fn item_row(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    let item = state.services.items.get_by_id(id).await?;
+    Ok(ItemRowPartial { item }.render_response())
+}
+
+/// Edit form — swapped in place of the row, carrying the item's current version
+pub async fn edit_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    let item = state.services.items.get_by_id(id).await?;
+    let form = ItemForm::from(&item);
+    Ok(ItemEditPartial { item, form }.render_response())
+}
+
+/// Save an edit — returns the updated row, or a 409 conflict fragment
+/// (with the latest values and the user's unsaved input) when the version is stale
+pub async fn update_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+    Form(update): Form<UpdateItemForm>,
+) -> AppResult<Response> {
+    let mut form = ItemForm {
+        title: update.title,
+        description: update.description,
+        tags: update.tags,
+        due: update.due,
+        parent: update.parent,
+        errors: FieldErrors::default(),
+    };
+    if let Err(errors) = form.validate() {
+        let item = state.services.items.get_by_id(id).await?;
+        form.errors = errors.clone();
+        let html = ItemEditPartial { item, form }.render_response();
+        return Err(AppError::invalid_form(
+            errors,
+            format!("#item-{}", id),
+            html,
+        ));
+    }
+
+    let before = state.services.items.get_by_id(id).await?;
+    let result = state
+        .services
+        .items
+        .update(id, form.changes(), update.version, &actor)
+        .await;
+
+    match result {
+        Ok(item) => {
+            let mut events = Vec::new();
+            if item.tags != before.tags {
+                events.push("tags-changed");
+            }
+            // A move changes where the row belongs, so the list reloads
+            if item.parent_id != before.parent_id {
+                events.push("items-changed");
+            }
+
+            let row = ItemRowPartial { item }.render_response();
+            if events.is_empty() {
+                Ok(row.into_response())
+            } else {
+                Ok(([("HX-Trigger", events.join(", "))], row).into_response())
+            }
+        }
+        // Tags and the other fields passed `validate`, so this is the parent
+        Err(AppError::Validation(msg)) => Err(parent_error(before, form, msg)),
+        Err(AppError::Conflict(_)) => {
+            let item = state.services.items.get_by_id(id).await?;
+            let fragment = ItemConflictPartial { item, form };
+            Ok((StatusCode::CONFLICT, fragment.render_response()).into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The edit form again, with a rejected parent — nothing was saved
+fn parent_error(item: Item, mut form: ItemForm, msg: String) -> AppError {
+    let mut errors = FieldErrors::default();
+    errors.add("parent", msg);
+    form.errors = errors.clone();
+    let target = format!("#item-{}", item.id);
+    let html = ItemEditPartial { item, form }.render_response();
+    AppError::invalid_form(errors, target, html)
+}
+
+/// Subtasks — the item's live children, loaded when the row's "Subtasks"
+/// disclosure is opened
+pub async fn item_children(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    let parent = state.services.items.get_by_id(id).await?;
+    let items = state.services.items.list_children(id).await?;
+
+    Ok(ItemChildrenPartial { parent, items }.render_response())
+}
+
+/// Add subtask — returns the refreshed subtasks plus the parent's progress,
+/// swapped out-of-band
+pub async fn create_child(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+    Form(form): Form<SubtaskForm>,
+) -> AppResult<impl IntoResponse> {
+    let title = form.title.trim();
+    if title.is_empty() {
+        return Err(AppError::validation("Title is required"));
+    } else if title.chars().count() > TITLE_MAX_CHARS {
+        return Err(AppError::validation(format!(
+            "Title must be at most {} characters",
+            TITLE_MAX_CHARS
+        )));
+    }
+
+    state
+        .services
+        .items
+        .create_child(id, title.to_string(), &actor)
+        .await?;
+    let parent = state.services.items.get_by_id(id).await?;
+    let items = state.services.items.list_children(id).await?;
+    let progress = ItemProgressPartial {
+        item: parent.clone(),
+    }
+    .render_response();
+    let children = ItemChildrenPartial { parent, items }.render_response();
+
+    Ok(Html(format!(
+        "{}{}",
+        children.0,
+        crate::render::oob_swap(&format!("item-progress-{}", id), &progress.0)
+    )))
+}
+
+/// The progress of `item`'s parent, as an out-of-band swap ("" for a
+/// top-level item) — appended when a subtask is toggled or trashed
+async fn parent_progress(state: &AppState, item: &Item) -> AppResult<String> {
+    let Some(parent_id) = item.parent_id else {
+        return Ok(String::new());
+    };
+    let parent = state.services.items.get_by_id(parent_id).await?;
+    let progress = ItemProgressPartial { item: parent }.render_response();
+    Ok(crate::render::oob_swap(
+        &format!("item-progress-{}", parent_id),
+        &progress.0,
+    ))
+}
+
+/// Item history — the recorded changes, newest first; loaded when the row's
+/// "History" disclosure is opened
+pub async fn item_history(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+) -> AppResult<impl IntoResponse> {
+    let events = state.services.items.history(id).await?;
+
+    Ok(ItemHistoryPartial { events }.render_response())
+}
+
+/// Toggle item — flips the done flag and returns the updated row
+pub async fn toggle_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+) -> AppResult<impl IntoResponse> {
+    let item = state.services.items.toggle_done(id, &actor).await?;
+    let progress = parent_progress(&state, &item).await?;
+
+    let row = ItemRowPartial { item }.render_response();
+    Ok(Html(format!("{}{}", row.0, progress)))
+}
+
+/// Reorder items — posted by the sortable list after a drag-and-drop, with
+/// the ids of its rows in their new order. The list reloads itself on the
+/// triggered `items-changed`.
+pub async fn reorder_items(
+    State(state): State<Arc<AppState>>,
+    HtmlForm(form): HtmlForm<ReorderForm>,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.reorder(&form.order).await?;
+
+    Ok((StatusCode::NO_CONTENT, [("HX-Trigger", "items-changed")]))
+}
+
+/// Bulk action on the checked rows — one batch call (a single transaction),
+/// then the refreshed list, in its current filter, plus a summary fragment
+/// swapped out-of-band into `#item-bulk-summary`.
+pub async fn bulk_items(
+    State(state): State<Arc<AppState>>,
+    Path(action): Path<BulkAction>,
+    Actor(actor): Actor,
+    HtmlForm(form): HtmlForm<BulkForm>,
+) -> AppResult<Response> {
+    if form.selected.is_empty() {
+        return Err(AppError::validation("Select at least one item first"));
+    }
+
+    let items = &state.services.items;
+    let ids = &form.selected;
+    let (message, trigger) = match action {
+        BulkAction::Done => {
+            let n = items.bulk_set_done(ids, true, &actor).await?;
+            (format!("{} marked done", item_count(n)), None)
+        }
+        BulkAction::Pending => {
+            let n = items.bulk_set_done(ids, false, &actor).await?;
+            (format!("{} marked pending", item_count(n)), None)
+        }
+        BulkAction::Delete => {
+            let n = items.bulk_delete(ids, &actor).await?;
+            (
+                format!("{} moved to the trash", item_count(n)),
+                Some("trash-changed, tags-changed"),
+            )
+        }
+        BulkAction::Tag => {
+            let n = items.bulk_add_tag(ids, &form.new_tag, &actor).await?;
+            let tag = form.new_tag.trim().to_lowercase();
+            (
+                format!("{} tagged {}", item_count(n), tag),
+                Some("tags-changed"),
+            )
+        }
+    };
+
+    let query = form.query();
+    let (list_items, next_url) = item_page(&state, &query, None).await?;
+    let list = ItemListPartial {
+        items: list_items,
+        next_url,
+        sortable: query.sort == ItemSort::Manual,
+    }
+    .render_response();
+    let summary = BulkSummaryPartial { message }.render_response();
+    let body = Html(format!(
+        "{}{}",
+        list.0,
+        crate::render::oob_swap("item-bulk-summary", &summary.0)
+    ));
+
+    Ok(match trigger {
+        Some(events) => ([("HX-Trigger", events)], body).into_response(),
+        None => body.into_response(),
+    })
+}
+
+/// "1 item" / "7 items"
+fn item_count(n: usize) -> String {
+    if n == 1 {
+        "1 item".to_string()
+    } else {
+        format!("{} items", n)
+    }
+}
+
+/// Delete item — moves it to the trash and swaps its row for an "Undo"
+/// placeholder; `HX-Trigger: trash-changed` refreshes the trash view.
+pub async fn delete_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+) -> AppResult<impl IntoResponse> {
+    let item = state.services.items.delete(id, &actor).await?;
+    let progress = parent_progress(&state, &item).await?;
+
+    let placeholder = ItemDeletedPartial {
+        item,
+        grace_ms: UNDO_GRACE_MS,
+    }
+    .render_response();
+    Ok((
+        [("HX-Trigger", "trash-changed")],
+        Html(format!("{}{}", placeholder.0, progress)),
+    ))
+}
+
+/// Trash view — deleted items with restore and purge actions
+pub async fn item_trash(State(state): State<Arc<AppState>>) -> AppResult<impl IntoResponse> {
+    let entries = state
+        .services
+        .items
+        .list_trash()
+        .await?
+        .into_iter()
+        .map(TrashEntry::from)
+        .collect();
+
+    Ok(ItemTrashPartial { entries }.render_response())
+}
+
+/// Restore item — used by both "Undo" and the trash view. Nothing is swapped;
+/// the list and trash reload themselves on the triggered events.
+pub async fn restore_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    Actor(actor): Actor,
+    session: CurrentSession,
+) -> AppResult<impl IntoResponse> {
+    let item = state.services.items.restore(id, &actor).await?;
+    session.push_flash(FlashLevel::Success, format!("Restored \"{}\"", item.title))?;
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        [("HX-Trigger", "items-changed, trash-changed")],
+    ))
+}
+
+/// Purge item — permanently removes a trashed item, its subtasks and all
+/// their attached files and comments
+pub async fn purge_item(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<u32>,
+    session: CurrentSession,
+) -> AppResult<impl IntoResponse> {
+    let subtree = state.services.items.subtree(id).await?;
+    let mut attachments = Vec::new();
+    for &item_id in &subtree {
+        attachments.extend(state.services.attachments.list(item_id).await?);
+    }
+    state.services.items.purge(id).await?;
+    // The item is gone either way; anything left behind is only logged
+    if let Err(e) = state.services.attachments.discard(&attachments).await {
+        tracing::warn!(item_id = id, error = %e, "attachment cleanup failed");
+    }
+    if let Err(e) = state.services.comments.discard(&subtree).await {
+        tracing::warn!(item_id = id, error = %e, "comment cleanup failed");
+    }
+    session.push_flash(FlashLevel::Info, "Item deleted permanently")?;
+
+    Ok((StatusCode::NO_CONTENT, [("HX-Trigger", "trash-changed")]))
+}
+
+/// Tag list — every tag in use with its item count, linking to the filtered list
+pub async fn tag_list(State(state): State<Arc<AppState>>) -> AppResult<impl IntoResponse> {
+    let tags = state.services.items.list_tags().await?;
+    Ok(TagListPartial { tags }.render_response())
+}
+
+/// Delete tag — removes the tag from every item; the list and tag list
+/// reload themselves on the triggered events
+pub async fn delete_tag(
+    State(state): State<Arc<AppState>>,
+    Actor(actor): Actor,
+    Path(name): Path<String>,
+) -> AppResult<impl IntoResponse> {
+    state.services.items.delete_tag(&name, &actor).await?;
+
+    Ok((
+        StatusCode::NO_CONTENT,
+        [("HX-Trigger", "items-changed, tags-changed")],
+    ))
+}
+
+/// Active search — ranked item matches with the query terms highlighted.
+/// Driven by `hx-trigger="keyup changed delay:300ms"` on the search input.
+pub async fn item_search(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ItemSearchQuery>,
+) -> AppResult<impl IntoResponse> {
+    let query = params.q.unwrap_or_default().trim().to_string();
+    let terms = search_terms(&query);
+
+    let hits = state
+        .services
+        .items
+        .search(&query, SEARCH_LIMIT)
+        .await?
+        .into_iter()
+        .map(|item| SearchHit {
+            id: item.id,
+            done: item.done,
+            title: highlight(&item.title, &terms),
+            description: highlight(&item.description, &terms),
+        })
+        .collect();
+
+    Ok(ItemSearchPartial { query, hits }.render_response())
 }
 
 /// Greeting partial — demonstrates HTMX form submission returning a fragment
@@ -65,3 +643,432 @@ pub async fn greeting(Query(params): Query<GreetingQuery>) -> impl IntoResponse
 pub struct GreetingQuery {
     pub name: Option<String>,
 }
+
+#[derive(Deserialize)]
+pub struct ItemListQuery {
+    pub after: Option<u32>,
+    /// Set by the filter controls: the user changed the filter, so the new
+    /// URL gets its own history entry
+    #[serde(default)]
+    pub push: bool,
+}
+
+/// Create/edit form state: the submitted values plus any per-field errors.
+/// Rendered by `partials/item_form.html` and `partials/item_edit.html`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ItemForm {
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    /// Comma-separated tag names, as typed
+    #[serde(default)]
+    pub tags: String,
+    /// Due date from a `datetime-local` input; blank for none
+    #[serde(default)]
+    pub due: String,
+    /// Parent item number (edit form only); blank for a top-level item
+    #[serde(default)]
+    pub parent: String,
+    #[serde(skip_deserializing)]
+    pub errors: FieldErrors,
+}
+
+impl ItemForm {
+    /// Field rules shared by the create and edit forms (and import rows)
+    pub(crate) fn validate(&self) -> Result<(), FieldErrors> {
+        let mut errors = FieldErrors::default();
+
+        let title = self.title.trim();
+        if title.is_empty() {
+            errors.add("title", "Title is required");
+        } else if title.chars().count() > TITLE_MAX_CHARS {
+            errors.add(
+                "title",
+                format!("Title must be at most {} characters", TITLE_MAX_CHARS),
+            );
+        }
+
+        if self.description.trim().chars().count() > DESCRIPTION_MAX_CHARS {
+            errors.add(
+                "description",
+                format!(
+                    "Description must be at most {} characters",
+                    DESCRIPTION_MAX_CHARS
+                ),
+            );
+        }
+
+        if let Err(msg) = parse_tags(&self.tags) {
+            errors.add("tags", msg);
+        }
+
+        if let Err(msg) = parse_due(&self.due) {
+            errors.add("due", msg);
+        }
+
+        if let Err(msg) = parse_parent(&self.parent) {
+            errors.add("parent", msg);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Normalized tag names — call after `validate` has passed
+    pub(crate) fn tag_list(&self) -> Vec<String> {
+        parse_tags(&self.tags).unwrap_or_default()
+    }
+
+    /// Parsed due date — call after `validate` has passed
+    pub(crate) fn due_at(&self) -> Option<DateTime<Utc>> {
+        parse_due(&self.due).unwrap_or_default()
+    }
+
+    /// Parsed parent id — call after `validate` has passed
+    pub(crate) fn parent_id(&self) -> Option<u32> {
+        parse_parent(&self.parent).unwrap_or_default()
+    }
+
+    /// Every field, trimmed and parsed — call after `validate` has passed
+    pub(crate) fn changes(&self) -> ItemChanges {
+        ItemChanges {
+            title: self.title.trim().to_string(),
+            description: self.description.trim().to_string(),
+            tags: self.tag_list(),
+            due_at: self.due_at(),
+            parent_id: self.parent_id(),
+        }
+    }
+}
+
+impl From<&Item> for ItemForm {
+    fn from(item: &Item) -> Self {
+        Self {
+            title: item.title.clone(),
+            description: item.description.clone(),
+            tags: item.tags.join(", "),
+            due: item
+                .due_at
+                .map(|at| at.format("%Y-%m-%dT%H:%M").to_string())
+                .unwrap_or_default(),
+            parent: item.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+            errors: FieldErrors::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct UpdateItemForm {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: String,
+    #[serde(default)]
+    pub due: String,
+    #[serde(default)]
+    pub parent: String,
+    pub version: u32,
+}
+
+#[derive(Deserialize)]
+pub struct SubtaskForm {
+    #[serde(default)]
+    pub title: String,
+}
+
+/// Repeated `order=<id>` fields, one per row (needs `axum_extra`'s form
+/// extractor, as `axum::Form` can't collect repeated keys)
+#[derive(Deserialize)]
+pub struct ReorderForm {
+    #[serde(default)]
+    pub order: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BulkAction {
+    Done,
+    Pending,
+    Delete,
+    Tag,
+}
+
+/// Bulk toolbar submission: the checked rows (row checkboxes join the
+/// toolbar form via `form="item-bulk"`), the tag to add, and the list's
+/// current filter so the refreshed list matches what the user was viewing
+#[derive(Deserialize)]
+pub struct BulkForm {
+    #[serde(default)]
+    pub selected: Vec<u32>,
+    #[serde(default)]
+    pub new_tag: String,
+    #[serde(default)]
+    pub status: StatusFilter,
+    #[serde(default)]
+    pub sort: ItemSort,
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tag: Option<String>,
+}
+
+impl BulkForm {
+    fn query(&self) -> ItemQuery {
+        ItemQuery {
+            status: self.status,
+            sort: self.sort,
+            tag: self.tag.clone(),
+            top_level: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ItemSearchQuery {
+    pub q: Option<String>,
+}
+
+/// A trashed item with its deletion time preformatted for display
+#[derive(Debug, Clone, Serialize)]
+pub struct TrashEntry {
+    pub item: Item,
+    pub deleted: String,
+}
+
+impl From<Item> for TrashEntry {
+    fn from(item: Item) -> Self {
+        let deleted = item
+            .deleted_at
+            .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+            .unwrap_or_default();
+        Self { item, deleted }
+    }
+}
+
+/// A search result with its title and description split into highlight segments
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub id: u32,
+    pub done: bool,
+    pub title: Vec<Segment>,
+    pub description: Vec<Segment>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::session::Session;
+    use crate::test_support::test_state;
+    use axum::{
+        body::Body,
+        http::{header, Request},
+        routing::{delete, get, post, put},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    /// The partial routes under test, behind the session the middleware
+    /// would otherwise provide
+    fn test_app(state: &Arc<AppState>, session: &CurrentSession) -> Router {
+        Router::new()
+            .route("/partials/item-list", get(item_list))
+            .route("/partials/items/reorder", post(reorder_items))
+            .route("/partials/items/bulk/:action", post(bulk_items))
+            .route("/partials/items/:id/restore", post(restore_item))
+            .route("/partials/items/:id/purge", delete(purge_item))
+            .route("/partials/items/:id", put(update_item))
+            .layer(axum::Extension(session.clone()))
+            .with_state(state.clone())
+    }
+
+    /// An HTMX form submission
+    fn htmx_form(method: &str, uri: &str, body: &str) -> Request<Body> {
+        Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("hx-request", "true")
+            .header(header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn header_str<'a>(response: &'a Response, name: &str) -> Option<&'a str> {
+        response.headers().get(name).map(|v| v.to_str().unwrap())
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_item_list_pushes_only_filter_changes() {
+        let state = test_state().await;
+        let app = test_app(&state, &CurrentSession::new(Session::new(), false));
+        let history_header = |uri: &'static str, current_url: &'static str| {
+            let app = app.clone();
+            async move {
+                let request = Request::get(uri)
+                    .header("hx-request", "true")
+                    .header("hx-current-url", current_url)
+                    .body(Body::empty())
+                    .unwrap();
+                let response = app.oneshot(request).await.unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                ["HX-Push-Url", "HX-Replace-Url"]
+                    .into_iter()
+                    .find(|name| response.headers().contains_key(*name))
+            }
+        };
+        let canonical = "http://localhost/items?status=all&sort=manual";
+
+        // The list loading into a fresh page only tidies up its URL
+        assert_eq!(
+            history_header("/partials/item-list", "http://localhost/items").await,
+            Some("HX-Replace-Url")
+        );
+        // A reload on `items-changed` is not navigation at all
+        assert_eq!(history_header("/partials/item-list", canonical).await, None);
+        // The user picking another filter is
+        assert_eq!(
+            history_header("/partials/item-list?status=done&push=true", canonical).await,
+            Some("HX-Push-Url")
+        );
+        // Another page showing the list keeps its own URL
+        assert_eq!(
+            history_header("/partials/item-list", "http://localhost/demo").await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_edit_saves_or_answers_with_conflict_and_form_errors() {
+        let state = test_state().await;
+        let app = test_app(&state, &CurrentSession::new(Session::new(), false));
+        let version = state.services.items.get_by_id(2).await.unwrap().version;
+        let save = |version: u32, parent: &str| {
+            let body = format!(
+                "title=Add+database&tags=db&parent={}&version={}",
+                parent, version
+            );
+            app.clone()
+                .oneshot(htmx_form("PUT", "/partials/items/2", &body))
+        };
+
+        // New tags refresh the tag list
+        let response = save(version, "").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_str(&response, "hx-trigger"), Some("tags-changed"));
+
+        // The version the form started from is gone
+        let response = save(version, "").await.unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert!(body_text(response).await.contains("Edit conflict"));
+
+        // A bad parent re-renders the form over the row
+        let response = save(version + 1, "99").await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(header_str(&response, "hx-retarget"), Some("#item-2"));
+        assert!(body_text(response).await.contains("There is no item #99"));
+
+        // A move reloads the list, since the row belongs elsewhere now
+        let response = save(version + 1, "1").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_str(&response, "hx-trigger"), Some("items-changed"));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_actions_return_the_list_with_a_summary() {
+        let state = test_state().await;
+        let app = test_app(&state, &CurrentSession::new(Session::new(), false));
+        let bulk = |action: &str, body: &str| {
+            let uri = format!("/partials/items/bulk/{}", action);
+            app.clone().oneshot(htmx_form("POST", &uri, body))
+        };
+
+        // Item 1 is already done
+        let response = bulk("done", "selected=1&selected=2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(header_str(&response, "hx-trigger"), None);
+        let html = body_text(response).await;
+        assert!(html.contains("id=\"item-bulk-summary\" hx-swap-oob"));
+        assert!(html.contains("1 item marked done"));
+
+        let response = bulk("delete", "selected=2&selected=3").await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            header_str(&response, "hx-trigger"),
+            Some("trash-changed, tags-changed")
+        );
+        assert!(body_text(response)
+            .await
+            .contains("2 items moved to the trash"));
+
+        // Errors go to the toast, and nothing changes
+        let response = bulk("done", "").await.unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(header_str(&response, "hx-retarget"), Some("#error-toast"));
+        let response = bulk("pending", "selected=1&selected=99").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(state.services.items.get_by_id(1).await.unwrap().done);
+    }
+
+    #[tokio::test]
+    async fn test_restore_and_purge_answer_no_content_with_triggers() {
+        let state = test_state().await;
+        let session = CurrentSession::new(Session::new(), false);
+        let app = test_app(&state, &session);
+        let send = |method: &str, uri: &str| app.clone().oneshot(htmx_form(method, uri, ""));
+
+        state.services.items.delete(3, "tester").await.unwrap();
+        let response = send("POST", "/partials/items/3/restore").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(
+            header_str(&response, "hx-trigger"),
+            Some("items-changed, trash-changed")
+        );
+        assert_eq!(session.drain_flashes()[0].message, "Restored \"Deploy\"");
+
+        // Only trashed items can be purged
+        let response = send("DELETE", "/partials/items/3/purge").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        state.services.items.delete(3, "tester").await.unwrap();
+        let response = send("DELETE", "/partials/items/3/purge").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header_str(&response, "hx-trigger"), Some("trash-changed"));
+        assert!(state.services.items.subtree(3).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reorder_answers_no_content_and_reloads_the_list() {
+        let state = test_state().await;
+        let app = test_app(&state, &CurrentSession::new(Session::new(), false));
+        let reorder = |body: &str| {
+            app.clone()
+                .oneshot(htmx_form("POST", "/partials/items/reorder", body))
+        };
+
+        let response = reorder("order=3&order=1&order=2").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(header_str(&response, "hx-trigger"), Some("items-changed"));
+        let order: Vec<u32> = state
+            .services
+            .items
+            .list_all()
+            .await
+            .unwrap()
+            .iter()
+            .map(|i| i.id)
+            .collect();
+        assert_eq!(order, [3, 1, 2]);
+
+        let response = reorder("order=3&order=99").await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -4,70 +4,83 @@
 //! - Debug: minijinja hot-reloads templates from disk  
 //! - Release: askama compiles templates into the binary
 
-use axum::{extract::State, http::header, response::IntoResponse};
+use axum::{
+    extract::{Query, State},
+    response::IntoResponse,
+};
 use std::sync::Arc;
 
+use crate::error::AppResult;
+use crate::handlers::partials::ItemForm;
 use crate::models::AppState;
-use crate::services::session::SESSION_COOKIE;
+use crate::services::items::{ItemQuery, Tag};
+use crate::services::session::{CurrentSession, Flash};
 
 // Define pages using the macro — one line per page instead of ~20!
-crate::define_page!(HomePage, "pages/home.html", { current_page: &'static str, csrf_token: String });
-crate::define_page!(AboutPage, "pages/about.html", { current_page: &'static str, csrf_token: String });
-crate::define_page!(DemoPage, "pages/demo.html", { current_page: &'static str, csrf_token: String });
-
-/// Extract session ID from request cookies
-fn get_session_id(headers: &axum::http::HeaderMap) -> Option<String> {
-    headers
-        .get(header::COOKIE)
-        .and_then(|v| v.to_str().ok())
-        .and_then(|cookies| {
-            cookies.split(';').find_map(|c| {
-                let c = c.trim();
-                c.strip_prefix(&format!("{}=", SESSION_COOKIE))
-                    .map(|v| v.to_string())
-            })
-        })
-}
+crate::define_page!(HomePage, "pages/home.html", { current_page: &'static str, csrf_token: String, flashes: Vec<Flash> });
+crate::define_page!(AboutPage, "pages/about.html", { current_page: &'static str, csrf_token: String, flashes: Vec<Flash> });
+crate::define_page!(DemoPage, "pages/demo.html", { current_page: &'static str, csrf_token: String, flashes: Vec<Flash> });
+crate::define_page!(ItemsPage, "pages/items.html", {
+    current_page: &'static str,
+    csrf_token: String,
+    flashes: Vec<Flash>,
+    form: ItemForm,
+    status: &'static str,
+    sort: &'static str,
+    tag: String,
+    tags: Vec<Tag>
+});
 
 // =============================================================================
 // Page Handlers — thin wrappers that delegate to templates
 // =============================================================================
 
-pub async fn home_page(
-    State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
-) -> impl IntoResponse {
-    let sid = get_session_id(&headers).unwrap_or_default();
-    let csrf_token = state.services.csrf.generate_token(&sid);
+pub async fn home_page(session: CurrentSession) -> impl IntoResponse {
     HomePage {
         current_page: "home",
-        csrf_token,
+        csrf_token: session.csrf_token(),
+        flashes: session.drain_flashes(),
     }
     .render_response()
 }
 
-pub async fn about_page(
-    State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
-) -> impl IntoResponse {
-    let sid = get_session_id(&headers).unwrap_or_default();
-    let csrf_token = state.services.csrf.generate_token(&sid);
+pub async fn about_page(session: CurrentSession) -> impl IntoResponse {
     AboutPage {
         current_page: "about",
-        csrf_token,
+        csrf_token: session.csrf_token(),
+        flashes: session.drain_flashes(),
     }
     .render_response()
 }
 
-pub async fn demo_page(
-    State(state): State<Arc<AppState>>,
-    headers: axum::http::HeaderMap,
-) -> impl IntoResponse {
-    let sid = get_session_id(&headers).unwrap_or_default();
-    let csrf_token = state.services.csrf.generate_token(&sid);
+pub async fn demo_page(session: CurrentSession) -> impl IntoResponse {
     DemoPage {
         current_page: "demo",
-        csrf_token,
+        csrf_token: session.csrf_token(),
+        flashes: session.drain_flashes(),
     }
     .render_response()
 }
+
+/// Items page — `?status=`, `?sort=` and `?tag=` preselect the list filter
+/// (these are the URLs the list pushes); unrecognized values fall back to
+/// the defaults.
+pub async fn items_page(
+    State(state): State<Arc<AppState>>,
+    session: CurrentSession,
+    filter: Option<Query<ItemQuery>>,
+) -> AppResult<impl IntoResponse> {
+    let filter = filter.map(|Query(f)| f).unwrap_or_default();
+    let tags = state.services.items.list_tags().await?;
+    Ok(ItemsPage {
+        current_page: "items",
+        csrf_token: session.csrf_token(),
+        flashes: session.drain_flashes(),
+        form: ItemForm::default(),
+        status: filter.status.as_str(),
+        sort: filter.sort.as_str(),
+        tag: filter.tag.unwrap_or_default(),
+        tags,
+    }
+    .render_response())
+}
@@ -0,0 +1,585 @@
+//! Import/Export Handlers — items in and out as CSV or JSON
+//!
+//! Exports stream every live item a page at a time, so memory stays flat
+//! however long the list is. Imports take two steps: the upload is parsed and
+//! validated into a preview, then the rows that passed are committed in a
+//! single transaction.
+
+use axum::{
+    body::Body,
+    extract::{Multipart, Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+    Form,
+};
+use chrono::Utc;
+use futures_util::stream;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::error::{AppError, AppResult};
+use crate::models::AppState;
+use crate::services::items::{Item, ItemQuery, NewItem};
+
+use super::partials::ItemForm;
+use super::Actor;
+
+/// Items fetched per query while streaming an export
+const EXPORT_BATCH: u32 = 200;
+/// Largest accepted upload
+const IMPORT_MAX_BYTES: usize = 1024 * 1024;
+/// Most rows accepted in one import
+const IMPORT_MAX_ROWS: usize = 1000;
+/// Column order of exported CSV files; imports read columns by name
+const CSV_HEADER: [&str; 9] = [
+    "id",
+    "parent_id",
+    "title",
+    "description",
+    "done",
+    "tags",
+    "due_at",
+    "created_at",
+    "updated_at",
+];
+
+crate::define_partial!(ImportPreviewPartial, "partials/import_preview.html", {
+    file_name: String,
+    rows: Vec<ImportRow>,
+    valid: usize,
+    invalid: usize,
+    payload: String
+});
+
+crate::define_partial!(ImportResultPartial, "partials/import_result.html", {
+    imported: usize,
+    skipped: Vec<ImportRow>
+});
+
+// =============================================================================
+// Export
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    fn content_type(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Encode one batch of items; `first`/`last` add the header or brackets
+    fn encode(self, items: &[Item], first: bool, last: bool) -> AppResult<Vec<u8>> {
+        let records = items.iter().map(ExportRecord::from);
+        match self {
+            ExportFormat::Csv => {
+                let mut writer = csv::WriterBuilder::new()
+                    .has_headers(false)
+                    .from_writer(Vec::new());
+                if first {
+                    writer.write_record(CSV_HEADER).map_err(export_error)?;
+                }
+                for record in records {
+                    writer.serialize(record).map_err(export_error)?;
+                }
+                writer
+                    .into_inner()
+                    .map_err(|e| AppError::internal(e.to_string()))
+            }
+            ExportFormat::Json => {
+                let mut out = Vec::new();
+                if first {
+                    out.push(b'[');
+                }
+                for (i, record) in records.enumerate() {
+                    if !(first && i == 0) {
+                        out.push(b',');
+                    }
+                    out.push(b'\n');
+                    serde_json::to_writer(&mut out, &record)
+                        .map_err(|e| AppError::internal(e.to_string()))?;
+                }
+                if last {
+                    out.extend_from_slice(b"\n]\n");
+                }
+                Ok(out)
+            }
+        }
+    }
+}
+
+fn export_error(err: csv::Error) -> AppError {
+    AppError::internal(err.to_string())
+}
+
+/// One exported item — tags are a comma-separated string in both formats,
+/// so a file round-trips through import unchanged. Subtasks name their
+/// parent by its `id` in the same file.
+#[derive(Serialize)]
+struct ExportRecord<'a> {
+    id: u32,
+    parent_id: Option<u32>,
+    title: &'a str,
+    description: &'a str,
+    done: bool,
+    tags: String,
+    /// RFC 3339, empty when the item has no due date
+    due_at: String,
+    created_at: String,
+    updated_at: String,
+}
+
+impl<'a> From<&'a Item> for ExportRecord<'a> {
+    fn from(item: &'a Item) -> Self {
+        Self {
+            id: item.id,
+            parent_id: item.parent_id,
+            title: &item.title,
+            description: &item.description,
+            done: item.done,
+            tags: item.tags.join(", "),
+            due_at: item.due_at.map(|at| at.to_rfc3339()).unwrap_or_default(),
+            created_at: item.created_at.to_rfc3339(),
+            updated_at: item.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// Download every live item, in manual order, as CSV or JSON
+pub async fn export_items(
+    State(state): State<Arc<AppState>>,
+    Path(format): Path<ExportFormat>,
+) -> Response {
+    let items = state.services.items.clone();
+
+    // State is the cursor of the next page: `None` once the last page is out
+    let chunks = stream::unfold(Some((None, true)), move |cursor| {
+        let items = items.clone();
+        async move {
+            let (after, first) = cursor?;
+            let query = ItemQuery::default();
+            let page = match items.list_page(&query, after, EXPORT_BATCH).await {
+                Ok(page) => page,
+                Err(e) => {
+                    tracing::error!(error = %e, "export aborted");
+                    return Some((Err(e), None));
+                }
+            };
+            let last = page.next_cursor.is_none();
+            let chunk = format.encode(&page.items, first, last);
+            Some((chunk, page.next_cursor.map(|next| (Some(next), false))))
+        }
+    });
+
+    let disposition = format!(
+        "attachment; filename=\"items-{}.{}\"",
+        Utc::now().format("%Y%m%d"),
+        format.extension()
+    );
+    (
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(chunks),
+    )
+        .into_response()
+}
+
+// =============================================================================
+// Import
+// =============================================================================
+
+/// One parsed row of an upload. The preview posts the rows back as its
+/// payload, so parse errors travel with them to the commit step.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportRow {
+    /// 1-based line (CSV) or array position (JSON), for error reports
+    pub line: usize,
+    /// The row's `id` in the file, which other rows' `parent_id` refers to
+    #[serde(default)]
+    pub id: Option<u32>,
+    #[serde(default)]
+    pub parent_id: Option<u32>,
+    pub title: String,
+    pub description: String,
+    pub done: bool,
+    pub tags: String,
+    pub due: String,
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+impl ImportRow {
+    /// Apply the item form's rules, appending any failures to `errors`
+    fn validate(mut self) -> Self {
+        if let Err(errors) = self.form().validate() {
+            for msg in errors.messages() {
+                // Rows posted back from the preview already carry theirs
+                if !self.errors.iter().any(|e| e == msg) {
+                    self.errors.push(msg.to_string());
+                }
+            }
+        }
+        self
+    }
+
+    fn form(&self) -> ItemForm {
+        ItemForm {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            tags: self.tags.clone(),
+            due: self.due.clone(),
+            ..ItemForm::default()
+        }
+    }
+
+    fn to_new_item(&self, parent: Option<usize>) -> NewItem {
+        NewItem {
+            title: self.title.trim().to_string(),
+            description: self.description.trim().to_string(),
+            done: self.done,
+            tags: self.form().tag_list(),
+            due_at: self.form().due_at(),
+            parent,
+        }
+    }
+
+    fn add_error(&mut self, msg: String) {
+        if !self.errors.contains(&msg) {
+            self.errors.push(msg);
+        }
+    }
+}
+
+/// Rows name their parent by its `id` in the same file. A parent that isn't
+/// in the file, or a chain of parents that loops, is an error on the row.
+fn check_parents(rows: &mut [ImportRow]) {
+    let parents: HashMap<u32, Option<u32>> = rows
+        .iter()
+        .filter_map(|row| Some((row.id?, row.parent_id)))
+        .collect();
+    for row in rows.iter_mut() {
+        let Some(parent_id) = row.parent_id else {
+            continue;
+        };
+        if !parents.contains_key(&parent_id) {
+            row.add_error(format!("Parent {} is not in this file", parent_id));
+            continue;
+        }
+        // A chain longer than the file must come round again
+        let mut next = Some(parent_id);
+        for _ in 0..=parents.len() {
+            next = next.and_then(|id| parents.get(&id).copied().flatten());
+        }
+        if next.is_some() {
+            row.add_error("Its parents form a loop".to_string());
+        }
+    }
+}
+
+/// A row as written in the file — every column optional, unknown ones
+/// (timestamps) ignored
+#[derive(Deserialize)]
+struct RawRecord {
+    #[serde(default)]
+    id: Option<u32>,
+    #[serde(default)]
+    parent_id: Option<u32>,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    done: Option<RawFlag>,
+    #[serde(default)]
+    tags: Option<RawTags>,
+    #[serde(default)]
+    due_at: Option<String>,
+}
+
+/// `done` as JSON booleans, CSV text or 0/1
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawFlag {
+    Bool(bool),
+    Int(i64),
+    Text(String),
+}
+
+/// `tags` as a comma-separated string or a JSON array
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawTags {
+    List(Vec<String>),
+    Text(String),
+}
+
+impl RawRecord {
+    fn into_row(self, line: usize) -> ImportRow {
+        let mut row = ImportRow {
+            line,
+            id: self.id,
+            parent_id: self.parent_id,
+            title: self.title,
+            description: self.description,
+            tags: match self.tags {
+                Some(RawTags::List(tags)) => tags.join(", "),
+                Some(RawTags::Text(tags)) => tags,
+                None => String::new(),
+            },
+            due: self.due_at.unwrap_or_default(),
+            ..ImportRow::default()
+        };
+        match self.done.map(parse_flag).unwrap_or(Ok(false)) {
+            Ok(done) => row.done = done,
+            Err(msg) => row.errors.push(msg),
+        }
+        row.validate()
+    }
+}
+
+fn parse_flag(flag: RawFlag) -> Result<bool, String> {
+    match flag {
+        RawFlag::Bool(done) => Ok(done),
+        RawFlag::Int(0) => Ok(false),
+        RawFlag::Int(1) => Ok(true),
+        RawFlag::Int(n) => Err(format!("done must be true or false, not {}", n)),
+        RawFlag::Text(text) => match text.trim().to_ascii_lowercase().as_str() {
+            "" | "false" | "no" | "0" => Ok(false),
+            "true" | "yes" | "1" => Ok(true),
+            other => Err(format!("done must be true or false, not \"{}\"", other)),
+        },
+    }
+}
+
+/// A row that couldn't be read at all
+fn unreadable(line: usize, err: impl std::fmt::Display) -> ImportRow {
+    ImportRow {
+        line,
+        errors: vec![format!("Unreadable row: {}", err)],
+        ..ImportRow::default()
+    }
+}
+
+/// Parse an upload into validated rows — JSON when the name says so or the
+/// content starts with `[`, CSV (with a header row) otherwise
+fn parse_upload(file_name: &str, bytes: &[u8]) -> AppResult<Vec<ImportRow>> {
+    let is_json = file_name.to_ascii_lowercase().ends_with(".json")
+        || bytes.iter().find(|b| !b.is_ascii_whitespace()) == Some(&b'[');
+
+    let rows: Vec<ImportRow> = if is_json {
+        let values: Vec<serde_json::Value> = serde_json::from_slice(bytes)
+            .map_err(|e| AppError::validation(format!("Not a JSON array of items: {}", e)))?;
+        values
+            .into_iter()
+            .enumerate()
+            .map(
+                |(i, value)| match serde_json::from_value::<RawRecord>(value) {
+                    Ok(raw) => raw.into_row(i + 1),
+                    Err(e) => unreadable(i + 1, e),
+                },
+            )
+            .collect()
+    } else {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(bytes);
+        let headers = reader
+            .headers()
+            .map_err(|e| AppError::validation(format!("Not a CSV file: {}", e)))?;
+        if !headers.iter().any(|h| h == "title") {
+            return Err(AppError::validation(
+                "The CSV header row needs a \"title\" column",
+            ));
+        }
+        let headers = headers.clone();
+        // A quoted field may span lines, so each record reports where it starts
+        let line_of = |position: Option<&csv::Position>| position.map_or(0, |p| p.line() as usize);
+        reader
+            .records()
+            .map(|record| match record {
+                Ok(record) => {
+                    let line = line_of(record.position());
+                    match record.deserialize::<RawRecord>(Some(&headers)) {
+                        Ok(raw) => raw.into_row(line),
+                        Err(e) => unreadable(line, e),
+                    }
+                }
+                Err(e) => unreadable(line_of(e.position()), e),
+            })
+            .collect()
+    };
+
+    if rows.is_empty() {
+        return Err(AppError::validation("The file has no items to import"));
+    }
+    if rows.len() > IMPORT_MAX_ROWS {
+        return Err(AppError::validation(format!(
+            "Import at most {} items at a time",
+            IMPORT_MAX_ROWS
+        )));
+    }
+    let mut rows = rows;
+    check_parents(&mut rows);
+    Ok(rows)
+}
+
+/// Import preview — parse the uploaded file and show what would be created
+pub async fn import_preview(mut multipart: Multipart) -> AppResult<impl IntoResponse> {
+    let mut upload = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::bad_request(e.body_text()))?
+    {
+        if field.name() == Some("file") {
+            let file_name = field.file_name().unwrap_or("upload").to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| AppError::bad_request(e.body_text()))?;
+            upload = Some((file_name, bytes));
+        }
+    }
+
+    let (file_name, bytes) = upload
+        .filter(|(_, bytes)| !bytes.is_empty())
+        .ok_or_else(|| AppError::validation("Choose a CSV or JSON file to import"))?;
+    if bytes.len() > IMPORT_MAX_BYTES {
+        return Err(AppError::validation(format!(
+            "Import files must be at most {} KiB",
+            IMPORT_MAX_BYTES / 1024
+        )));
+    }
+
+    let rows = parse_upload(&file_name, &bytes)?;
+    let invalid = rows.iter().filter(|row| !row.errors.is_empty()).count();
+    let payload = serde_json::to_string(&rows).map_err(|e| AppError::internal(e.to_string()))?;
+    Ok(ImportPreviewPartial {
+        file_name,
+        valid: rows.len() - invalid,
+        invalid,
+        rows,
+        payload,
+    }
+    .render_response())
+}
+
+#[derive(Deserialize)]
+pub struct ImportCommitForm {
+    pub payload: String,
+}
+
+/// Import commit — re-validate the previewed rows and create the valid ones
+/// in one transaction
+pub async fn import_commit(
+    State(state): State<Arc<AppState>>,
+    Actor(actor): Actor,
+    Form(form): Form<ImportCommitForm>,
+) -> AppResult<impl IntoResponse> {
+    let rows: Vec<ImportRow> = serde_json::from_str(&form.payload)
+        .map_err(|_| AppError::bad_request("Import data is malformed; upload the file again"))?;
+    if rows.len() > IMPORT_MAX_ROWS {
+        return Err(AppError::validation(format!(
+            "Import at most {} items at a time",
+            IMPORT_MAX_ROWS
+        )));
+    }
+
+    // The payload came back from the browser, so check it all again
+    let mut rows: Vec<ImportRow> = rows.into_iter().map(ImportRow::validate).collect();
+    check_parents(&mut rows);
+    let (valid, skipped): (Vec<ImportRow>, Vec<ImportRow>) =
+        rows.into_iter().partition(|row| row.errors.is_empty());
+    if valid.is_empty() {
+        return Err(AppError::validation("None of the rows can be imported"));
+    }
+
+    // Subtasks whose parent row was skipped are imported top-level
+    let index: HashMap<u32, usize> = valid
+        .iter()
+        .enumerate()
+        .filter_map(|(i, row)| Some((row.id?, i)))
+        .collect();
+    let new_items = valid
+        .iter()
+        .map(|row| row.to_new_item(row.parent_id.and_then(|id| index.get(&id).copied())))
+        .collect();
+    let imported = state.services.items.import(new_items, &actor).await?;
+
+    Ok((
+        [("HX-Trigger", "items-changed, tags-changed")],
+        ImportResultPartial { imported, skipped }.render_response(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_upload_reads_csv_and_json_alike() {
+        let csv = "id,title,description,done,tags,due_at\n\
+                   1,Write docs,\"two\nlines\",true,\"docs, writing\",2025-01-31\n\
+                   2,,missing title,no,,\n\
+                   3,Ship it,,maybe,release,soon\n";
+        let rows = parse_upload("items.csv", csv.as_bytes()).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].line, 2);
+        // Counted from where each record starts, past the multi-line field
+        assert_eq!(rows[1].line, 4);
+        assert!(rows[0].errors.is_empty() && rows[0].done);
+        assert_eq!(rows[0].to_new_item(None).tags, vec!["docs", "writing"]);
+        assert_eq!(
+            rows[0].to_new_item(None).due_at.unwrap().to_rfc3339(),
+            "2025-01-31T23:59:00+00:00"
+        );
+        assert_eq!(rows[1].errors, vec!["Title is required"]);
+        assert!(rows[2].errors[0].contains("maybe"));
+        assert!(rows[2].errors[1].contains("soon"));
+
+        let json = r#"[{"title": "Write docs", "done": true, "tags": ["Docs", "writing"]},
+                       {"title": "Ship it", "done": 1},
+                       "not an object"]"#;
+        let rows = parse_upload("upload", json.as_bytes()).unwrap();
+        assert_eq!(rows[0].to_new_item(None).tags, vec!["docs", "writing"]);
+        assert!(rows[1].errors.is_empty() && rows[1].done);
+        assert!(rows[2].errors[0].starts_with("Unreadable row"));
+
+        assert!(parse_upload("items.csv", b"name\nfoo\n").is_err());
+        assert!(parse_upload("items.json", b"[]").is_err());
+    }
+
+    #[test]
+    fn test_parents_must_be_in_the_file_without_loops() {
+        let csv = "id,parent_id,title\n\
+                   1,,Release\n\
+                   2,1,Changelog\n\
+                   3,9,Orphan\n\
+                   4,5,Chicken\n\
+                   5,4,Egg\n";
+        let rows = parse_upload("items.csv", csv.as_bytes()).unwrap();
+        assert!(rows[..2].iter().all(|row| row.errors.is_empty()));
+        assert_eq!(rows[1].parent_id, Some(1));
+        assert_eq!(rows[2].errors, vec!["Parent 9 is not in this file"]);
+        assert_eq!(rows[3].errors, vec!["Its parents form a loop"]);
+        assert_eq!(rows[4].errors, vec!["Its parents form a loop"]);
+    }
+}
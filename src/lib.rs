@@ -26,5 +26,8 @@ pub mod render;
 pub mod services;
 pub mod utils;
 
+#[cfg(test)]
+mod test_support;
+
 pub use config::AppConfig;
-pub use error::{AppError, AppResult};
+pub use error::{AppError, AppResult, FieldErrors};
@@ -8,14 +8,16 @@
 //! - Server header stripping
 
 use axum::{
+    body::Body,
     extract::Request,
-    http::{header, Method, StatusCode},
+    http::{header, HeaderMap, Method, StatusCode},
     middleware::Next,
     response::{Html, IntoResponse, Response},
 };
 
 use crate::models::AppState;
-use crate::services::session::SESSION_COOKIE;
+use crate::render::oob_swap;
+use crate::services::session::{CurrentSession, Flash, Session, SessionAction, SESSION_COOKIE};
 use std::sync::Arc;
 
 /// SRI hash for the vendored htmx.min.js — update if the file changes.
@@ -25,7 +27,7 @@ const HTMX_SRI_HASH: &str =
 
 /// SRI hash for app.js — update if the file changes.
 const APP_SRI_HASH: &str =
-    "sha384-PMounJsLzecWPmGgUp+rmq81ao6CaK1vp02qhyBK66VebP1pIGgbYS+m14+AsFN5";
+    "sha384-eGcBTox49ns7Lg4IQBJ1JAxa6gb8iJK8yYqIkwQv6dV6JguO34GNLVA0mQTfIBIe";
 
 // ─── Security Headers ───────────────────────────────────────────────────────
 
@@ -124,7 +126,8 @@ pub async fn security_headers(request: Request, next: Next) -> Response {
 
 /// CSRF middleware — validates token on all state-changing requests.
 /// The token must be sent as `X-CSRF-Token` header (HTMX sends this automatically
-/// via `hx-headers` attribute on the body tag).
+/// via `hx-headers` attribute on the body tag). Runs inside `session_middleware`,
+/// whose resolved session it checks the token against.
 pub async fn csrf_protection(request: Request, next: Next) -> Response {
     let method = request.method().clone();
 
@@ -133,34 +136,24 @@ pub async fn csrf_protection(request: Request, next: Next) -> Response {
         return next.run(request).await;
     }
 
-    // Extract state and session cookie
+    // Extract state and the request's session
     let state = request.extensions().get::<Arc<AppState>>().cloned();
     let csrf_header = request
         .headers()
         .get("x-csrf-token")
         .and_then(|v| v.to_str().ok())
         .map(|s| s.to_string());
+    let session = request.extensions().get::<CurrentSession>().cloned();
 
-    let session_id = request
-        .headers()
-        .get(header::COOKIE)
-        .and_then(|v| v.to_str().ok())
-        .and_then(|cookies| {
-            cookies.split(';').find_map(|c| {
-                let c = c.trim();
-                c.strip_prefix(&format!("{}=", SESSION_COOKIE))
-                    .map(|v| v.to_string())
-            })
-        });
-
-    match (state, csrf_header, session_id) {
-        (Some(state), Some(token), Some(sid)) => {
-            // Verify session exists
-            if state.services.sessions.get(&sid).is_none() {
+    match (state, csrf_header, session) {
+        (Some(state), Some(token), Some(session)) => {
+            // A session minted for this very request means the cookie was
+            // missing, expired or unknown
+            if session.is_new() {
                 return csrf_error("Invalid session");
             }
             // Verify CSRF token
-            if !state.services.csrf.validate_token(&token, &sid) {
+            if !state.services.csrf.validate_token(&token, &session.id()) {
                 return csrf_error("Invalid CSRF token");
             }
             next.run(request).await
@@ -177,23 +170,27 @@ fn csrf_error(msg: &str) -> Response {
 </div>"#,
         msg
     );
-    (StatusCode::FORBIDDEN, Html(body)).into_response()
+    let mut response = (StatusCode::FORBIDDEN, Html(body)).into_response();
+
+    // Same toast target as AppError, so HTMX never swaps this into the page body
+    response.headers_mut().insert(
+        header::HeaderName::from_static("hx-retarget"),
+        header::HeaderValue::from_static("#error-toast"),
+    );
+    response.headers_mut().insert(
+        header::HeaderName::from_static("hx-reswap"),
+        header::HeaderValue::from_static("innerHTML"),
+    );
+
+    response
 }
 
 // ─── Session Middleware ─────────────────────────────────────────────────────
 
-/// Session middleware — ensures every request has a valid session.
-/// Creates a new session if none exists or if the session has expired.
-/// Injects CSRF token into response for HTMX to pick up.
-pub async fn session_middleware(request: Request, next: Next) -> Response {
-    let state = match request.extensions().get::<Arc<AppState>>().cloned() {
-        Some(s) => s,
-        None => return next.run(request).await,
-    };
-
-    // Try to extract existing session ID from cookie
-    let existing_sid = request
-        .headers()
+/// Session ID from the request's session cookie — the only place the cookie
+/// is read; everything else takes the resolved `CurrentSession`
+fn session_cookie(headers: &HeaderMap) -> Option<String> {
+    headers
         .get(header::COOKIE)
         .and_then(|v| v.to_str().ok())
         .and_then(|cookies| {
@@ -202,39 +199,150 @@ pub async fn session_middleware(request: Request, next: Next) -> Response {
                 c.strip_prefix(&format!("{}=", SESSION_COOKIE))
                     .map(|v| v.to_string())
             })
-        });
-
-    // Validate or create session
-    let (session, _is_new) = match existing_sid {
-        Some(ref sid) => {
-            match state.services.sessions.get(sid) {
-                Some(session) => {
-                    state.services.sessions.touch(sid);
-                    (session, false)
-                }
-                None => (state.services.sessions.create(), true), // Expired or invalid
-            }
-        }
-        None => (state.services.sessions.create(), true),
+        })
+}
+
+/// Append a `Set-Cookie` for the session cookie; `Max-Age=0` clears it
+fn set_session_cookie(response: &mut Response, value: &str, max_age: i64) {
+    let cookie_value = format!(
+        "{}={}; Path=/; Secure; HttpOnly; SameSite=Strict; Max-Age={}",
+        SESSION_COOKIE, value, max_age
+    );
+    response
+        .headers_mut()
+        .append(header::SET_COOKIE, cookie_value.parse().unwrap());
+}
+
+/// Crawler names and markers, each matched as a whole word of the User-Agent
+const BOT_WORDS: &[&str] = &[
+    "bot",
+    "crawler",
+    "spider",
+    "googlebot",
+    "bingbot",
+    "slurp",
+    "duckduckbot",
+    "baiduspider",
+    "yandexbot",
+    "applebot",
+    "facebookexternalhit",
+];
+
+/// Crawlers announce themselves in their User-Agent. They get pages like
+/// anyone else, but never a stored session, however many URLs they fetch.
+/// Besides the known names, any product token ending in "bot" counts
+/// ("AhrefsBot/7.0"), but not a word that merely contains it (a "Cubot" phone).
+fn is_bot(headers: &HeaderMap) -> bool {
+    let user_agent = headers
+        .get(header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let product_bot = user_agent
+        .split(|c: char| c.is_whitespace() || matches!(c, ';' | '(' | ')' | ','))
+        .filter_map(|token| token.split_once('/'))
+        .any(|(product, _)| product.ends_with("bot"));
+    product_bot
+        || user_agent
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| BOT_WORDS.contains(&word))
+}
+
+/// How old a session's `last_access` may get before a request refreshes it.
+/// Refreshing on every request (each stylesheet and script included) would
+/// cost store writes; the idle timeout is only this much less exact.
+const TOUCH_INTERVAL_SECS: i64 = 60;
+
+/// Session middleware — ensures every request has a valid session.
+/// Starts a new session if none exists or if the session has expired —
+/// stored only once the handler used it (see `CurrentSession::was_used`) —
+/// and hands it to the CSRF layer and handlers as a `CurrentSession` extension;
+/// session data a handler changed is saved once it has run, then any
+/// `SessionAction` the handler returned is carried out. The cookie lasts
+/// exactly as long as the session (see `SessionLifetime`).
+/// Injects CSRF token into response for HTMX to pick up.
+pub async fn session_middleware(mut request: Request, next: Next) -> Response {
+    let state = match request.extensions().get::<Arc<AppState>>().cloned() {
+        Some(s) => s,
+        None => return next.run(request).await,
+    };
+
+    // Validate or start a session
+    let sessions = &state.services.sessions;
+    let existing = match session_cookie(request.headers()) {
+        Some(sid) => sessions.get(&sid).await,
+        None => None,
+    };
+    let (mut session, is_new) = match existing {
+        Some(session) => (session, false),
+        None => (Session::new(), true), // Missing, expired or invalid
     };
 
-    // Generate CSRF token for this session
+    // Generate CSRF token for this session. Tokens are checked by their
+    // signature, so the stored copy is only refreshed with `last_access`.
     let csrf_token = state.services.csrf.generate_token(&session.id);
-    state
-        .services
-        .sessions
-        .update_csrf(&session.id, &csrf_token);
+    let idle = chrono::Utc::now() - session.last_access;
+    if !is_new && idle.num_seconds() >= TOUCH_INTERVAL_SECS {
+        sessions.touch(&mut session).await;
+        sessions.update_csrf(&session.id, &csrf_token).await;
+    }
+    session.csrf_token = csrf_token.clone();
+
+    let current = CurrentSession::new(session.clone(), is_new);
+    request.extensions_mut().insert(current.clone());
+    let is_htmx = request.headers().contains_key("hx-request");
+    let is_bot = is_bot(request.headers());
 
     let mut response = next.run(request).await;
+    // Full pages render their own flashes via base.html
+    if is_htmx {
+        response = append_flashes(response, &current).await;
+    }
+
+    if is_new {
+        // Nothing to keep: no cookie, no token
+        if !current.was_used() || is_bot {
+            return response;
+        }
+        sessions.insert(&session).await;
+    }
+    if let Some(data) = current.take_changes() {
+        sessions.save_data(&session.id, &data).await;
+    }
+
+    if let Some(remember) = current.take_remember() {
+        if let Some(updated) = sessions.set_remember(&session.id, remember).await {
+            session = updated;
+        }
+    }
+
+    let mut csrf_token = csrf_token;
+    match response.extensions_mut().remove::<SessionAction>() {
+        None => {}
+        Some(SessionAction::Rotate) => {
+            if let Some(rotated) = sessions.rotate(&session.id).await {
+                session = rotated;
+                csrf_token = state.services.csrf.generate_token(&session.id);
+                sessions.update_csrf(&session.id, &csrf_token).await;
+            }
+        }
+        Some(action) => {
+            if let SessionAction::DestroyAllSharing(key) = action {
+                if let Some(value) = current.data_value(&key) {
+                    sessions.destroy_where(&key, &value).await;
+                }
+            }
+            sessions.destroy(&session.id).await;
+            // Expire the cookie; the next request starts a fresh session
+            set_session_cookie(&mut response, "", 0);
+            return response;
+        }
+    }
 
     // Set session cookie (always — refreshes expiry)
-    let cookie_value = format!(
-        "{}={}; Path=/; HttpOnly; SameSite=Strict; Max-Age=3600",
-        SESSION_COOKIE, session.id
-    );
-    response
-        .headers_mut()
-        .append(header::SET_COOKIE, cookie_value.parse().unwrap());
+    let expires_at = sessions.lifetime().expires_at(&session);
+    let max_age = (expires_at - chrono::Utc::now()).num_seconds().max(0);
+    set_session_cookie(&mut response, &session.id, max_age);
 
     // Inject CSRF token as a response header for HTMX to read
     response.headers_mut().insert(
@@ -242,12 +350,39 @@ pub async fn session_middleware(request: Request, next: Next) -> Response {
         csrf_token.parse().unwrap(),
     );
 
-    // Periodically cleanup expired sessions (every ~100th request)
-    if rand::random::<u8>() < 3 {
-        state.services.sessions.cleanup_expired();
+    response
+}
+
+crate::define_partial!(FlashMessagesPartial, "partials/flash_messages.html", {
+    flashes: Vec<Flash>
+});
+
+/// Deliver pending flash messages with an HTMX response, as an out-of-band
+/// swap into `#flash-messages`. htmx ignores the body of a 204 or an error
+/// fragment's OOB content, so only successful HTML responses take them —
+/// anything else leaves them queued for the next one.
+async fn append_flashes(response: Response, session: &CurrentSession) -> Response {
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/html"));
+    if response.status() != StatusCode::OK || !is_html || !session.has_flashes() {
+        return response;
     }
 
-    response
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let flashes = FlashMessagesPartial {
+        flashes: session.drain_flashes(),
+    }
+    .render_response();
+    let mut html = bytes.to_vec();
+    html.extend_from_slice(oob_swap("flash-messages", &flashes.0).as_bytes());
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(html))
 }
 
 // ─── Request Logging ────────────────────────────────────────────────────────
@@ -272,3 +407,208 @@ pub async fn request_logger(request: Request, next: Next) -> Response {
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_state;
+    use axum::{
+        middleware,
+        routing::{get, post},
+        Router,
+    };
+    use tower::Service;
+
+    /// Session id from a response's `Set-Cookie` ("" when it clears the cookie)
+    fn set_cookie_sid(response: &Response) -> String {
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        let value = cookie.split(';').next().unwrap();
+        value[SESSION_COOKIE.len() + 1..].to_string()
+    }
+
+    fn post_with(sid: &str, token: &str, uri: &str) -> Request {
+        Request::post(uri)
+            .header(header::COOKIE, format!("{}={}", SESSION_COOKIE, sid))
+            .header("x-csrf-token", token)
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_handlers_rotate_and_destroy_sessions_via_response() {
+        let state = test_state().await;
+        let sessions = state.services.sessions.clone();
+        // A router is always ready, so requests go straight to `call`
+        let mut app = Router::new()
+            .route(
+                "/login",
+                post(|session: CurrentSession| async move {
+                    session.set("user", &"alice").unwrap();
+                    (SessionAction::Rotate, "welcome")
+                }),
+            )
+            .route(
+                "/logout-everywhere",
+                post(|| async { (SessionAction::DestroyAllSharing("user".into()), "bye") }),
+            )
+            .layer(middleware::from_fn(csrf_protection))
+            .layer(middleware::from_fn(session_middleware))
+            .layer(axum::Extension(state.clone()));
+
+        let session = sessions.create().await;
+        let token = state.services.csrf.generate_token(&session.id);
+        let response = app
+            .call(post_with(&session.id, &token, "/login"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let rotated = set_cookie_sid(&response);
+        assert_ne!(rotated, session.id);
+        assert!(sessions.get(&session.id).await.is_none());
+        let stored = sessions.get(&rotated).await.unwrap();
+        assert_eq!(stored.get::<String>("user").as_deref(), Some("alice"));
+
+        // The fresh token belongs to the new id
+        let token = response.headers()["x-csrf-token"].to_str().unwrap();
+        assert!(state.services.csrf.validate_token(token, &rotated));
+        let response = app
+            .call(post_with(&rotated, token, "/logout-everywhere"))
+            .await
+            .unwrap();
+        assert_eq!(set_cookie_sid(&response), "");
+        assert!(sessions.get(&rotated).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_flashes_wait_for_an_htmx_html_response() {
+        let state = test_state().await;
+        let mut app = Router::new()
+            .route(
+                "/save",
+                post(|session: CurrentSession| async move {
+                    session
+                        .push_flash(crate::services::session::FlashLevel::Success, "Saved <b>")
+                        .unwrap();
+                    StatusCode::NO_CONTENT
+                }),
+            )
+            .route("/list", get(|| async { Html("<ul></ul>") }))
+            .layer(middleware::from_fn(session_middleware))
+            .layer(axum::Extension(state.clone()));
+        let sid = state.services.sessions.create().await.id;
+        let list = |sid: &str| {
+            Request::get("/list")
+                .header(header::COOKIE, format!("{}={}", SESSION_COOKIE, sid))
+                .header("hx-request", "true")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let body = |response: Response| async {
+            let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            String::from_utf8(bytes.to_vec()).unwrap()
+        };
+
+        // The 204 itself can't show it
+        let response = app.call(post_with(&sid, "", "/save")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let html = body(app.call(list(&sid)).await.unwrap()).await;
+        assert!(html.starts_with("<ul></ul><div id=\"flash-messages\" hx-swap-oob"));
+        assert!(html.contains("alert-success"));
+        assert!(html.contains("Saved &lt;b&gt;"));
+
+        // Shown once
+        assert_eq!(body(app.call(list(&sid)).await.unwrap()).await, "<ul></ul>");
+    }
+
+    #[tokio::test]
+    async fn test_sessions_are_only_stored_once_used() {
+        let state = test_state().await;
+        let mut app = Router::new()
+            .route("/healthz", get(|| async { "ok" }))
+            .route(
+                "/page",
+                get(|session: CurrentSession| async move { session.csrf_token() }),
+            )
+            .layer(middleware::from_fn(session_middleware))
+            .layer(axum::Extension(state.clone()));
+        let get_as = |uri: &str, user_agent: &str| {
+            Request::get(uri)
+                .header(header::USER_AGENT, user_agent)
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let response = app.call(get_as("/healthz", "probe/1.0")).await.unwrap();
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+        assert!(!response.headers().contains_key("x-csrf-token"));
+
+        let crawler = "Mozilla/5.0 (compatible; Googlebot/2.1)";
+        let response = app.call(get_as("/page", crawler)).await.unwrap();
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+
+        let response = app.call(get_as("/page", "Mozilla/5.0")).await.unwrap();
+        let sid = set_cookie_sid(&response);
+        assert!(state.services.sessions.get(&sid).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_last_access_is_written_at_most_once_a_minute() {
+        let state = test_state().await;
+        let sessions = state.services.sessions.clone();
+        let mut app = Router::new()
+            .route("/static/app.css", get(|| async { "body {}" }))
+            .layer(middleware::from_fn(session_middleware))
+            .layer(axum::Extension(state.clone()));
+        let fetch = |sid: &str| {
+            Request::get("/static/app.css")
+                .header(header::COOKIE, format!("{}={}", SESSION_COOKIE, sid))
+                .body(Body::empty())
+                .unwrap()
+        };
+
+        let mut session = Session::new();
+        session.last_access -= chrono::Duration::minutes(2);
+        sessions.insert(&session).await;
+
+        app.call(fetch(&session.id)).await.unwrap();
+        let touched = sessions.get(&session.id).await.unwrap();
+        assert!(touched.last_access > session.last_access);
+        assert!(!touched.csrf_token.is_empty());
+
+        // Right after, nothing is written
+        app.call(fetch(&session.id)).await.unwrap();
+        let again = sessions.get(&session.id).await.unwrap();
+        assert_eq!(again.last_access, touched.last_access);
+        assert_eq!(again.csrf_token, touched.csrf_token);
+    }
+
+    #[test]
+    fn test_is_bot_matches_crawlers_not_lookalikes() {
+        let is_bot_ua = |user_agent: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::USER_AGENT, user_agent.parse().unwrap());
+            is_bot(&headers)
+        };
+
+        for crawler in [
+            "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+            "Mozilla/5.0 (compatible; bingbot/2.0; +http://www.bing.com/bingbot.htm)",
+            "Mozilla/5.0 (compatible; Yahoo! Slurp; http://help.yahoo.com/help/us/ysearch/slurp)",
+            "Mozilla/5.0 (compatible; AhrefsBot/7.0; +http://ahrefs.com/robot/)",
+            "facebookexternalhit/1.1",
+        ] {
+            assert!(is_bot_ua(crawler), "{}", crawler);
+        }
+        for browser in [
+            "Mozilla/5.0 (Linux; Android 10; CUBOT X20 PRO) AppleWebKit/537.36 \
+             (KHTML, like Gecko) Chrome/91.0.4472.120 Mobile Safari/537.36",
+            "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0",
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) Robotics-Lab Edition",
+        ] {
+            assert!(!is_bot_ua(browser), "{}", browser);
+        }
+    }
+}
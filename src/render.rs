@@ -105,3 +105,9 @@ macro_rules! define_partial {
         }
     };
 }
+
+/// Wrap already-rendered HTML for an HTMX out-of-band swap, replacing the
+/// contents of the element with `id` alongside the response's main target.
+pub fn oob_swap(id: &str, html: &str) -> String {
+    format!(r#"<div id="{}" hx-swap-oob="innerHTML">{}</div>"#, id, html)
+}
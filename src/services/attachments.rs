@@ -0,0 +1,413 @@
+//! Attachment Service — files uploaded to items
+//!
+//! Metadata is kept in the `attachments` table, the bytes in a `BlobStorage`
+//! under a random key. The content type is sniffed from the file itself and
+//! only a short allow-list is accepted; whatever the client claims is ignored.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::{Arc, RwLock};
+
+use super::storage::{new_key, BlobStorage};
+use crate::error::{AppError, AppResult};
+
+/// Largest file accepted for upload
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+
+/// Most files a single item can carry
+pub const MAX_ATTACHMENTS_PER_ITEM: usize = 20;
+
+/// Longest stored file name, in characters
+const MAX_FILE_NAME_CHARS: usize = 120;
+
+/// A file attached to an item
+#[derive(Debug, Clone, Serialize)]
+pub struct Attachment {
+    pub id: u32,
+    pub item_id: u32,
+    /// Display name, sanitized from the uploaded name
+    pub file_name: String,
+    /// Sniffed from the file contents at upload time
+    pub content_type: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+    #[serde(skip)]
+    storage_key: String,
+}
+
+/// Content type by magic bytes; `None` for anything not on the allow-list.
+/// UTF-8 without NUL bytes counts as plain text.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"\x1f\x8b", "application/gzip"),
+    ];
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(sig, _)| bytes.starts_with(sig)) {
+        return Some(content_type);
+    }
+    if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    if !bytes.contains(&0) && std::str::from_utf8(bytes).is_ok() {
+        return Some("text/plain; charset=utf-8");
+    }
+    None
+}
+
+/// Display name for an uploaded file: the last path component, without
+/// control characters, trimmed to a sane length
+pub fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_CHARS)
+        .collect();
+    let cleaned = cleaned.trim().trim_start_matches('.');
+    if cleaned.is_empty() {
+        "file".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+/// Validate an upload; returns its display name and sniffed content type
+fn checked_upload(file_name: &str, bytes: &[u8]) -> AppResult<(String, &'static str)> {
+    if bytes.is_empty() {
+        return Err(AppError::validation("The file is empty"));
+    }
+    if bytes.len() > MAX_ATTACHMENT_BYTES {
+        return Err(file_too_large());
+    }
+    let content_type = sniff_content_type(bytes).ok_or_else(|| {
+        AppError::validation(
+            "Only images (PNG, JPEG, GIF, WebP), PDF, gzip and plain text files can be attached",
+        )
+    })?;
+    Ok((sanitize_file_name(file_name), content_type))
+}
+
+/// Rejection for uploads over `MAX_ATTACHMENT_BYTES`
+pub fn file_too_large() -> AppError {
+    AppError::validation(format!(
+        "Files may be at most {} MiB",
+        MAX_ATTACHMENT_BYTES / (1024 * 1024)
+    ))
+}
+
+fn attachment_not_found(id: u32) -> AppError {
+    AppError::not_found(format!("Attachment {}", id))
+}
+
+fn too_many_attachments(item_id: u32) -> AppError {
+    AppError::validation(format!(
+        "Item {} already has {} attachments",
+        item_id, MAX_ATTACHMENTS_PER_ITEM
+    ))
+}
+
+/// Attachment service trait. Callers check the item itself is live.
+#[async_trait]
+pub trait AttachmentService: Send + Sync {
+    /// An item's attachments, oldest first
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Attachment>>;
+    async fn get(&self, id: u32) -> AppResult<Attachment>;
+    /// An attachment together with its contents
+    async fn read(&self, id: u32) -> AppResult<(Attachment, Vec<u8>)>;
+    /// Validate, sniff and store an upload
+    async fn add(&self, item_id: u32, file_name: &str, bytes: Vec<u8>) -> AppResult<Attachment>;
+    async fn delete(&self, id: u32) -> AppResult<Attachment>;
+    /// Remove these attachments and their files — used after their item was
+    /// purged, when the rows may already be gone
+    async fn discard(&self, attachments: &[Attachment]) -> AppResult<()>;
+}
+
+/// In-memory attachment metadata (prototyping, tests)
+pub struct InMemoryAttachmentService {
+    attachments: RwLock<Vec<Attachment>>,
+    next_id: RwLock<u32>,
+    storage: Arc<dyn BlobStorage>,
+}
+
+impl InMemoryAttachmentService {
+    pub fn new(storage: Arc<dyn BlobStorage>) -> Self {
+        Self {
+            attachments: RwLock::new(Vec::new()),
+            next_id: RwLock::new(1),
+            storage,
+        }
+    }
+}
+
+#[async_trait]
+impl AttachmentService for InMemoryAttachmentService {
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Attachment>> {
+        Ok(self
+            .attachments
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|a| a.item_id == item_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get(&self, id: u32) -> AppResult<Attachment> {
+        self.attachments
+            .read()
+            .unwrap()
+            .iter()
+            .find(|a| a.id == id)
+            .cloned()
+            .ok_or_else(|| attachment_not_found(id))
+    }
+
+    async fn read(&self, id: u32) -> AppResult<(Attachment, Vec<u8>)> {
+        let attachment = self.get(id).await?;
+        let bytes = self.storage.get(&attachment.storage_key).await?;
+        Ok((attachment, bytes))
+    }
+
+    async fn add(&self, item_id: u32, file_name: &str, bytes: Vec<u8>) -> AppResult<Attachment> {
+        let (file_name, content_type) = checked_upload(file_name, &bytes)?;
+        if self.list(item_id).await?.len() >= MAX_ATTACHMENTS_PER_ITEM {
+            return Err(too_many_attachments(item_id));
+        }
+        let storage_key = new_key();
+        self.storage.put(&storage_key, &bytes).await?;
+
+        let mut next_id = self.next_id.write().unwrap();
+        let attachment = Attachment {
+            id: *next_id,
+            item_id,
+            file_name,
+            content_type: content_type.to_string(),
+            size: bytes.len() as u64,
+            created_at: Utc::now(),
+            storage_key,
+        };
+        *next_id += 1;
+        self.attachments.write().unwrap().push(attachment.clone());
+        Ok(attachment)
+    }
+
+    async fn delete(&self, id: u32) -> AppResult<Attachment> {
+        let attachment = {
+            let mut attachments = self.attachments.write().unwrap();
+            let pos = attachments
+                .iter()
+                .position(|a| a.id == id)
+                .ok_or_else(|| attachment_not_found(id))?;
+            attachments.remove(pos)
+        };
+        self.storage.delete(&attachment.storage_key).await?;
+        Ok(attachment)
+    }
+
+    async fn discard(&self, attachments: &[Attachment]) -> AppResult<()> {
+        self.attachments
+            .write()
+            .unwrap()
+            .retain(|a| attachments.iter().all(|d| d.id != a.id));
+        for attachment in attachments {
+            self.storage.delete(&attachment.storage_key).await?;
+        }
+        Ok(())
+    }
+}
+
+/// SQLite-backed attachment metadata
+pub struct SqliteAttachmentService {
+    pool: SqlitePool,
+    storage: Arc<dyn BlobStorage>,
+}
+
+impl SqliteAttachmentService {
+    pub fn new(pool: SqlitePool, storage: Arc<dyn BlobStorage>) -> Self {
+        Self { pool, storage }
+    }
+}
+
+const ATTACHMENT_COLUMNS: &str =
+    "id, item_id, file_name, content_type, size, storage_key, created_at";
+
+/// Row type for `attachments`
+#[derive(sqlx::FromRow)]
+struct AttachmentRow {
+    id: i64,
+    item_id: i64,
+    file_name: String,
+    content_type: String,
+    size: i64,
+    storage_key: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<AttachmentRow> for Attachment {
+    fn from(row: AttachmentRow) -> Self {
+        Attachment {
+            id: row.id as u32,
+            item_id: row.item_id as u32,
+            file_name: row.file_name,
+            content_type: row.content_type,
+            size: row.size as u64,
+            created_at: row.created_at,
+            storage_key: row.storage_key,
+        }
+    }
+}
+
+#[async_trait]
+impl AttachmentService for SqliteAttachmentService {
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Attachment>> {
+        let rows = sqlx::query_as::<_, AttachmentRow>(&format!(
+            "SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE item_id = ? ORDER BY id"
+        ))
+        .bind(item_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Attachment::from).collect())
+    }
+
+    async fn get(&self, id: u32) -> AppResult<Attachment> {
+        sqlx::query_as::<_, AttachmentRow>(&format!(
+            "SELECT {ATTACHMENT_COLUMNS} FROM attachments WHERE id = ?"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Attachment::from)
+        .ok_or_else(|| attachment_not_found(id))
+    }
+
+    async fn read(&self, id: u32) -> AppResult<(Attachment, Vec<u8>)> {
+        let attachment = self.get(id).await?;
+        let bytes = self.storage.get(&attachment.storage_key).await?;
+        Ok((attachment, bytes))
+    }
+
+    async fn add(&self, item_id: u32, file_name: &str, bytes: Vec<u8>) -> AppResult<Attachment> {
+        let (file_name, content_type) = checked_upload(file_name, &bytes)?;
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM attachments WHERE item_id = ?")
+            .bind(item_id as i64)
+            .fetch_one(&self.pool)
+            .await?;
+        if count as usize >= MAX_ATTACHMENTS_PER_ITEM {
+            return Err(too_many_attachments(item_id));
+        }
+
+        // Write the file first: a failed insert leaves a stray blob to clean
+        // up, never a row pointing at nothing
+        let storage_key = new_key();
+        self.storage.put(&storage_key, &bytes).await?;
+        let inserted = sqlx::query_as::<_, AttachmentRow>(&format!(
+            "INSERT INTO attachments (item_id, file_name, content_type, size, storage_key, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?) RETURNING {ATTACHMENT_COLUMNS}"
+        ))
+        .bind(item_id as i64)
+        .bind(&file_name)
+        .bind(content_type)
+        .bind(bytes.len() as i64)
+        .bind(&storage_key)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await;
+
+        match inserted {
+            Ok(row) => Ok(row.into()),
+            Err(e) => {
+                if let Err(cleanup) = self.storage.delete(&storage_key).await {
+                    tracing::warn!(error = %cleanup, "orphaned attachment blob");
+                }
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn delete(&self, id: u32) -> AppResult<Attachment> {
+        let attachment: Attachment = sqlx::query_as::<_, AttachmentRow>(&format!(
+            "DELETE FROM attachments WHERE id = ? RETURNING {ATTACHMENT_COLUMNS}"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Attachment::from)
+        .ok_or_else(|| attachment_not_found(id))?;
+        self.storage.delete(&attachment.storage_key).await?;
+        Ok(attachment)
+    }
+
+    async fn discard(&self, attachments: &[Attachment]) -> AppResult<()> {
+        for attachment in attachments {
+            sqlx::query("DELETE FROM attachments WHERE id = ?")
+                .bind(attachment.id as i64)
+                .execute(&self.pool)
+                .await?;
+            self.storage.delete(&attachment.storage_key).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::services::storage::LocalDiskStorage;
+
+    #[test]
+    fn test_sniffing_ignores_names_and_rejects_unknown_types() {
+        assert_eq!(
+            sniff_content_type(b"\x89PNG\r\n\x1a\n...."),
+            Some("image/png")
+        );
+        assert_eq!(
+            sniff_content_type(b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(
+            sniff_content_type("notes: ünïcode".as_bytes()),
+            Some("text/plain; charset=utf-8")
+        );
+        assert_eq!(sniff_content_type(b"MZ\x90\0\x03\0\0\0"), None);
+        assert_eq!(sniff_content_type(b"\xff\xfe<\0h\0t\0m\0l\0"), None);
+
+        assert_eq!(sanitize_file_name("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_file_name("C:\\Users\\me\\a\r\nb.txt"), "ab.txt");
+        assert_eq!(sanitize_file_name(".."), "file");
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_attachments_live_on_disk() {
+        let root = std::env::temp_dir().join(format!("attachments-{}", uuid::Uuid::new_v4()));
+        let service = SqliteAttachmentService::new(
+            db::init_memory_pool().await.unwrap(),
+            Arc::new(LocalDiskStorage::new(&root)),
+        );
+
+        let pdf = b"%PDF-1.7 minimal".to_vec();
+        let added = service.add(1, "report.pdf", pdf.clone()).await.unwrap();
+        assert_eq!(added.content_type, "application/pdf");
+        assert_eq!(added.size, pdf.len() as u64);
+        assert!(matches!(
+            service.add(1, "setup.exe", b"MZ\x90\0".to_vec()).await,
+            Err(AppError::Validation(_))
+        ));
+
+        let (found, bytes) = service.read(added.id).await.unwrap();
+        assert_eq!(found.file_name, "report.pdf");
+        assert_eq!(bytes, pdf);
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 1);
+
+        service.delete(added.id).await.unwrap();
+        assert!(service.list(1).await.unwrap().is_empty());
+        assert_eq!(std::fs::read_dir(&root).unwrap().count(), 0);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,284 @@
+//! Comment Service — discussion threads on items
+//!
+//! Comments are Markdown, signed with the writing session's actor label (as in
+//! item history) and listed oldest first. Only the author may delete a
+//! comment. With no accounts, authorship is just the session's actor label,
+//! so it is lost once that session rotates or expires; the comment can then
+//! only go when its item is purged.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use std::sync::RwLock;
+
+use crate::error::{AppError, AppResult};
+
+/// Longest comment accepted, in characters (mirrored as `maxlength`)
+pub const MAX_COMMENT_CHARS: usize = 2000;
+
+/// One comment on an item
+#[derive(Debug, Clone, Serialize)]
+pub struct Comment {
+    pub id: u32,
+    pub item_id: u32,
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Trimmed comment text, or why it can't be posted
+fn checked_body(body: &str) -> AppResult<String> {
+    let body = body.trim();
+    if body.is_empty() {
+        return Err(AppError::validation("Write something first"));
+    }
+    if body.chars().count() > MAX_COMMENT_CHARS {
+        return Err(AppError::validation(format!(
+            "Comments may be at most {} characters",
+            MAX_COMMENT_CHARS
+        )));
+    }
+    Ok(body.to_string())
+}
+
+fn comment_not_found(id: u32) -> AppError {
+    AppError::not_found(format!("Comment {}", id))
+}
+
+/// Comment service trait. Callers check the item itself is live.
+#[async_trait]
+pub trait CommentService: Send + Sync {
+    /// An item's comments, oldest first
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Comment>>;
+    async fn get(&self, id: u32) -> AppResult<Comment>;
+    /// Validate and post a comment by `author`
+    async fn add(&self, item_id: u32, author: &str, body: &str) -> AppResult<Comment>;
+    async fn delete(&self, id: u32) -> AppResult<Comment>;
+    /// Remove the threads of these items — used after they were purged,
+    /// when the rows may already be gone with them
+    async fn discard(&self, item_ids: &[u32]) -> AppResult<()>;
+}
+
+/// In-memory comments (prototyping, tests)
+pub struct InMemoryCommentService {
+    comments: RwLock<Vec<Comment>>,
+    next_id: RwLock<u32>,
+}
+
+impl InMemoryCommentService {
+    pub fn new() -> Self {
+        Self {
+            comments: RwLock::new(Vec::new()),
+            next_id: RwLock::new(1),
+        }
+    }
+}
+
+impl Default for InMemoryCommentService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl CommentService for InMemoryCommentService {
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Comment>> {
+        Ok(self
+            .comments
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|c| c.item_id == item_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get(&self, id: u32) -> AppResult<Comment> {
+        self.comments
+            .read()
+            .unwrap()
+            .iter()
+            .find(|c| c.id == id)
+            .cloned()
+            .ok_or_else(|| comment_not_found(id))
+    }
+
+    async fn add(&self, item_id: u32, author: &str, body: &str) -> AppResult<Comment> {
+        let body = checked_body(body)?;
+        let mut next_id = self.next_id.write().unwrap();
+        let comment = Comment {
+            id: *next_id,
+            item_id,
+            author: author.to_string(),
+            body,
+            created_at: Utc::now(),
+        };
+        *next_id += 1;
+        self.comments.write().unwrap().push(comment.clone());
+        Ok(comment)
+    }
+
+    async fn delete(&self, id: u32) -> AppResult<Comment> {
+        let mut comments = self.comments.write().unwrap();
+        let pos = comments
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| comment_not_found(id))?;
+        Ok(comments.remove(pos))
+    }
+
+    async fn discard(&self, item_ids: &[u32]) -> AppResult<()> {
+        self.comments
+            .write()
+            .unwrap()
+            .retain(|c| !item_ids.contains(&c.item_id));
+        Ok(())
+    }
+}
+
+/// SQLite-backed comments
+pub struct SqliteCommentService {
+    pool: SqlitePool,
+}
+
+impl SqliteCommentService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+const COMMENT_COLUMNS: &str = "id, item_id, author, body, created_at";
+
+/// Row type for `item_comments`
+#[derive(sqlx::FromRow)]
+struct CommentRow {
+    id: i64,
+    item_id: i64,
+    author: String,
+    body: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<CommentRow> for Comment {
+    fn from(row: CommentRow) -> Self {
+        Comment {
+            id: row.id as u32,
+            item_id: row.item_id as u32,
+            author: row.author,
+            body: row.body,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[async_trait]
+impl CommentService for SqliteCommentService {
+    async fn list(&self, item_id: u32) -> AppResult<Vec<Comment>> {
+        let rows = sqlx::query_as::<_, CommentRow>(&format!(
+            "SELECT {COMMENT_COLUMNS} FROM item_comments WHERE item_id = ? ORDER BY id"
+        ))
+        .bind(item_id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Comment::from).collect())
+    }
+
+    async fn get(&self, id: u32) -> AppResult<Comment> {
+        sqlx::query_as::<_, CommentRow>(&format!(
+            "SELECT {COMMENT_COLUMNS} FROM item_comments WHERE id = ?"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Comment::from)
+        .ok_or_else(|| comment_not_found(id))
+    }
+
+    async fn add(&self, item_id: u32, author: &str, body: &str) -> AppResult<Comment> {
+        let body = checked_body(body)?;
+        let row = sqlx::query_as::<_, CommentRow>(&format!(
+            "INSERT INTO item_comments (item_id, author, body, created_at) \
+             VALUES (?, ?, ?, ?) RETURNING {COMMENT_COLUMNS}"
+        ))
+        .bind(item_id as i64)
+        .bind(author)
+        .bind(&body)
+        .bind(Utc::now())
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(row.into())
+    }
+
+    async fn delete(&self, id: u32) -> AppResult<Comment> {
+        sqlx::query_as::<_, CommentRow>(&format!(
+            "DELETE FROM item_comments WHERE id = ? RETURNING {COMMENT_COLUMNS}"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Comment::from)
+        .ok_or_else(|| comment_not_found(id))
+    }
+
+    async fn discard(&self, item_ids: &[u32]) -> AppResult<()> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        for &item_id in item_ids {
+            sqlx::query("DELETE FROM item_comments WHERE item_id = ?")
+                .bind(item_id as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+    use crate::services::items::{InMemoryItemService, ItemService, SqliteItemService};
+
+    #[tokio::test]
+    async fn test_comments_thread_across_backends() {
+        let pool = db::init_memory_pool().await.unwrap();
+        let sqlite = SqliteCommentService::new(pool.clone());
+        let sqlite_items = SqliteItemService::new(pool);
+        let memory = InMemoryCommentService::new();
+        let memory_items = InMemoryItemService::new();
+        let backends: [(&dyn CommentService, &dyn ItemService); 2] =
+            [(&sqlite, &sqlite_items), (&memory, &memory_items)];
+
+        for (service, items) in backends {
+            let first = service.add(1, "session a", "  Looks good ").await.unwrap();
+            assert_eq!(first.body, "Looks good");
+            service.add(1, "session b", "Ship it").await.unwrap();
+            assert!(matches!(
+                service.add(1, "session a", " \n ").await,
+                Err(AppError::Validation(_))
+            ));
+            let too_long = "x".repeat(MAX_COMMENT_CHARS + 1);
+            assert!(service.add(1, "session a", &too_long).await.is_err());
+
+            let thread = service.list(1).await.unwrap();
+            let authors: Vec<&str> = thread.iter().map(|c| c.author.as_str()).collect();
+            assert_eq!(authors, ["session a", "session b"]);
+
+            service.delete(first.id).await.unwrap();
+            assert!(matches!(
+                service.delete(first.id).await,
+                Err(AppError::NotFound(_))
+            ));
+            assert_eq!(service.list(1).await.unwrap().len(), 1);
+
+            // The thread goes with its item when that is purged, the way
+            // the purge handler does it
+            let subtree = items.subtree(1).await.unwrap();
+            items.delete(1, "t").await.unwrap();
+            items.purge(1).await.unwrap();
+            service.discard(&subtree).await.unwrap();
+            assert!(service.list(1).await.unwrap().is_empty());
+        }
+    }
+}
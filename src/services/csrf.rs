@@ -12,6 +12,7 @@
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use rand::RngCore;
 use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
 
 /// CSRF token length in bytes (32 bytes = 256 bits)
 const TOKEN_BYTES: usize = 32;
@@ -28,6 +29,25 @@ impl CsrfSecret {
         Self(key)
     }
 
+    /// The secret kept in the database (`app_secrets`), created on first use.
+    /// Persisted sessions need it: their tokens must still validate after a
+    /// restart.
+    pub async fn load_or_create(pool: &SqlitePool) -> Result<Self, sqlx::Error> {
+        let candidate = Self::generate();
+        sqlx::query(
+            "INSERT INTO app_secrets (name, value) VALUES ('csrf', ?) \
+             ON CONFLICT (name) DO NOTHING",
+        )
+        .bind(&candidate.0)
+        .execute(pool)
+        .await?;
+        let (key,): (Vec<u8>,) =
+            sqlx::query_as("SELECT value FROM app_secrets WHERE name = 'csrf'")
+                .fetch_one(pool)
+                .await?;
+        Ok(Self(key))
+    }
+
     /// Generate a CSRF token bound to a session ID
     pub fn generate_token(&self, session_id: &str) -> String {
         // Random nonce
@@ -103,6 +123,18 @@ mod tests {
         assert!(!secret.validate_token("garbage", session));
     }
 
+    #[tokio::test]
+    async fn test_stored_secret_is_reused() {
+        let pool = crate::db::init_memory_pool().await.unwrap();
+        let token = CsrfSecret::load_or_create(&pool)
+            .await
+            .unwrap()
+            .generate_token("session");
+
+        let reloaded = CsrfSecret::load_or_create(&pool).await.unwrap();
+        assert!(reloaded.validate_token(&token, "session"));
+    }
+
     #[test]
     fn test_tokens_are_unique() {
         let secret = CsrfSecret::generate();
@@ -1,5 +1,6 @@
 //! Health Service — Server health and status information
 
+use async_trait::async_trait;
 use std::time::SystemTime;
 
 /// Health status information
@@ -12,8 +13,9 @@ pub struct HealthStatus {
 }
 
 /// Health service trait — allows mocking in tests
+#[async_trait]
 pub trait HealthService: Send + Sync {
-    fn get_status(&self) -> HealthStatus;
+    async fn get_status(&self) -> HealthStatus;
 }
 
 /// Default implementation using system time
@@ -27,8 +29,9 @@ impl DefaultHealthService {
     }
 }
 
+#[async_trait]
 impl HealthService for DefaultHealthService {
-    fn get_status(&self) -> HealthStatus {
+    async fn get_status(&self) -> HealthStatus {
         let uptime_seconds = SystemTime::now()
             .duration_since(self.start_time)
             .unwrap_or_default()
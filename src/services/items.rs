@@ -3,9 +3,16 @@
 //! Provides CRUD operations for items. Default implementation uses in-memory storage.
 //! Can be swapped for database-backed implementation (SQLx, etc.)
 
-use serde::{Deserialize, Serialize};
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Timelike, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::RwLock;
 
+use crate::error::{AppError, AppResult};
+use crate::utils::highlight::search_terms;
+
 /// Item data model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
@@ -13,52 +20,808 @@ pub struct Item {
     pub title: String,
     pub description: String,
     pub done: bool,
+    /// Incremented on every write — edits must name the version they started from
+    pub version: u32,
+    /// Normalized tag names, alphabetical
+    pub tags: Vec<String>,
+    /// Manual sort order (ascending); new items go last
+    pub position: u32,
+    /// The item this is a subtask of; `None` for top-level items
+    pub parent_id: Option<u32>,
+    /// Number of live subtasks (computed, not stored)
+    pub subtasks: u32,
+    /// How many of those are done — progress reads "3/5 done"
+    pub subtasks_done: u32,
+    /// Deadline, if any (minute precision, UTC)
+    pub due_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    /// Last change to the item's content or status
+    pub updated_at: DateTime<Utc>,
+    /// Set while the item sits in the trash; `None` for live items
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+/// A new item with its initial status and tags, e.g. one row of an import
+#[derive(Debug, Clone, Default)]
+pub struct NewItem {
+    pub title: String,
+    pub description: String,
+    pub done: bool,
+    pub tags: Vec<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// Parent item, as an index into the same batch
+    pub parent: Option<usize>,
+}
+
+/// The fields a user sets on an item through its form, applied together
+#[derive(Debug, Clone, Default)]
+pub struct ItemChanges {
+    pub title: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    /// The item to become a subtask of; `None` for a top-level item
+    pub parent_id: Option<u32>,
+}
+
+/// A tag in use, with the number of live items carrying it
+#[derive(Debug, Clone, Serialize)]
+pub struct Tag {
+    pub name: String,
+    pub item_count: u32,
+}
+
+/// What happened to an item, as recorded in its history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemEventKind {
+    Created,
+    Updated,
+    Toggled,
+    Deleted,
+    Restored,
+}
+
+impl ItemEventKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ItemEventKind::Created => "created",
+            ItemEventKind::Updated => "updated",
+            ItemEventKind::Toggled => "toggled",
+            ItemEventKind::Deleted => "deleted",
+            ItemEventKind::Restored => "restored",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        [
+            ItemEventKind::Created,
+            ItemEventKind::Updated,
+            ItemEventKind::Toggled,
+            ItemEventKind::Deleted,
+            ItemEventKind::Restored,
+        ]
+        .into_iter()
+        .find(|k| k.as_str() == kind)
+    }
+}
+
+impl std::fmt::Display for ItemEventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One entry in an item's history
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemEvent {
+    pub id: u32,
+    pub item_id: u32,
+    pub kind: ItemEventKind,
+    /// Who made the change, e.g. a session label from `session_actor`
+    pub actor: String,
+    pub at: DateTime<Utc>,
+    /// The fields the change touched, alphabetical
+    pub changes: Vec<FieldChange>,
+}
+
+/// A field's value before and after a change (empty where it had none,
+/// e.g. `before` of a created item)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub before: String,
+    pub after: String,
+}
+
+/// Old and new values of the fields an event touched, stored as JSON objects
+#[derive(Debug, Clone, Default)]
+struct EventValues {
+    old: serde_json::Map<String, serde_json::Value>,
+    new: serde_json::Map<String, serde_json::Value>,
+}
+
+impl EventValues {
+    /// The content fields a brand-new item was given
+    fn created(item: &Item) -> Self {
+        let mut values = Self::default();
+        values.new.insert("title".into(), item.title.clone().into());
+        if !item.description.is_empty() {
+            values
+                .new
+                .insert("description".into(), item.description.clone().into());
+        }
+        values.new.insert("done".into(), item.done.into());
+        if !item.tags.is_empty() {
+            values
+                .new
+                .insert("tags".into(), item.tags.join(", ").into());
+        }
+        if item.due_at.is_some() {
+            values.new.insert("due".into(), due_value(item.due_at));
+        }
+        if item.parent_id.is_some() {
+            values
+                .new
+                .insert("parent".into(), parent_value(item.parent_id));
+        }
+        values
+    }
+
+    /// The content fields that differ between two versions of an item
+    fn between(old: &Item, new: &Item) -> Self {
+        let mut values = Self::default();
+        if old.title != new.title {
+            values.set("title", old.title.clone(), new.title.clone());
+        }
+        if old.description != new.description {
+            values.set(
+                "description",
+                old.description.clone(),
+                new.description.clone(),
+            );
+        }
+        if old.done != new.done {
+            values.set("done", old.done, new.done);
+        }
+        if old.tags != new.tags {
+            values.set("tags", old.tags.join(", "), new.tags.join(", "));
+        }
+        if old.due_at != new.due_at {
+            values.set("due", due_value(old.due_at), due_value(new.due_at));
+        }
+        if old.parent_id != new.parent_id {
+            values.set(
+                "parent",
+                parent_value(old.parent_id),
+                parent_value(new.parent_id),
+            );
+        }
+        values
+    }
+
+    fn tags(old: &[String], new: &[String]) -> Self {
+        let mut values = Self::default();
+        values.set("tags", old.join(", "), new.join(", "));
+        values
+    }
+
+    fn done(new: bool) -> Self {
+        let mut values = Self::default();
+        values.set("done", !new, new);
+        values
+    }
+
+    fn set(
+        &mut self,
+        field: &str,
+        old: impl Into<serde_json::Value>,
+        new: impl Into<serde_json::Value>,
+    ) {
+        self.old.insert(field.into(), old.into());
+        self.new.insert(field.into(), new.into());
+    }
+
+    fn is_empty(&self) -> bool {
+        self.old.is_empty() && self.new.is_empty()
+    }
+
+    fn changes(&self) -> Vec<FieldChange> {
+        let display = |value: Option<&serde_json::Value>| match value {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(serde_json::Value::Null) | None => String::new(),
+            Some(other) => other.to_string(),
+        };
+        let fields: std::collections::BTreeSet<&String> =
+            self.old.keys().chain(self.new.keys()).collect();
+        fields
+            .into_iter()
+            .map(|field| FieldChange {
+                field: field.clone(),
+                before: display(self.old.get(field)),
+                after: display(self.new.get(field)),
+            })
+            .collect()
+    }
+}
+
+/// A due date as shown in history entries
+fn due_value(due_at: Option<DateTime<Utc>>) -> serde_json::Value {
+    due_at
+        .map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string().into())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// A parent as shown in history entries, e.g. "#12"; none for top level
+fn parent_value(parent_id: Option<u32>) -> serde_json::Value {
+    parent_id
+        .map(|id| format!("#{}", id).into())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+/// Longest allowed tag name
+pub const TAG_MAX_CHARS: usize = 32;
+
+/// Most tags a single item may carry
+pub const MAX_TAGS_PER_ITEM: usize = 10;
+
+/// Canonical form of a tag name: trimmed and lowercased. Names are limited to
+/// ASCII letters, digits, `-` and `_`, so they are URL-safe and never contain
+/// the `,` used to separate them.
+pub fn normalize_tag(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() {
+        return Err("Tags can't be empty".to_string());
+    }
+    if name.chars().count() > TAG_MAX_CHARS {
+        return Err(format!(
+            "Tag \"{}\" is longer than {} characters",
+            name, TAG_MAX_CHARS
+        ));
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Tag \"{}\" may only contain letters, digits, - and _",
+            name
+        ));
+    }
+    Ok(name)
+}
+
+/// Normalize a set of tag names: sorted, de-duplicated and within the
+/// per-item limit
+pub fn normalize_tags<'a>(names: impl IntoIterator<Item = &'a str>) -> Result<Vec<String>, String> {
+    let mut tags = names
+        .into_iter()
+        .map(normalize_tag)
+        .collect::<Result<Vec<_>, _>>()?;
+    tags.sort();
+    tags.dedup();
+    if tags.len() > MAX_TAGS_PER_ITEM {
+        return Err(format!("At most {} tags per item", MAX_TAGS_PER_ITEM));
+    }
+    Ok(tags)
+}
+
+/// Parse user input like `"urgent, backend"`; blank entries are ignored
+pub fn parse_tags(input: &str) -> Result<Vec<String>, String> {
+    normalize_tags(input.split(',').filter(|t| !t.trim().is_empty()))
+}
+
+/// Parse a due date as entered: blank for none, `YYYY-MM-DDTHH:MM` (a
+/// `datetime-local` input), a bare `YYYY-MM-DD` meaning the end of that day,
+/// or RFC 3339 (as exported). Times without an offset are taken as UTC, and
+/// everything is truncated to the minute.
+pub fn parse_due(input: &str) -> Result<Option<DateTime<Utc>>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    let at = if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+        at.with_timezone(&Utc)
+    } else if let Ok(at) = NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M") {
+        at.and_utc()
+    } else if let Ok(at) = NaiveDateTime::parse_from_str(input, "%Y-%m-%dT%H:%M:%S") {
+        at.and_utc()
+    } else if let Ok(day) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        day.and_hms_opt(23, 59, 0).expect("valid time").and_utc()
+    } else {
+        return Err(format!("\"{}\" is not a date like 2025-01-31 14:30", input));
+    };
+    Ok(at.with_second(0).and_then(|at| at.with_nanosecond(0)))
+}
+
+/// Parse a parent item reference as entered: blank for a top-level item,
+/// otherwise an item number, optionally written `#12`
+pub fn parse_parent(input: &str) -> Result<Option<u32>, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Ok(None);
+    }
+    match input.trim_start_matches('#').parse::<u32>() {
+        Ok(id) if id > 0 => Ok(Some(id)),
+        _ => Err(format!("\"{}\" is not an item number", input)),
+    }
+}
+
+/// Which items a listing includes, by completion status
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatusFilter {
+    #[default]
+    All,
+    Pending,
+    Done,
+}
+
+impl StatusFilter {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Pending => "pending",
+            Self::Done => "done",
+        }
+    }
+
+    fn matches(self, item: &Item) -> bool {
+        match self {
+            Self::All => true,
+            Self::Pending => !item.done,
+            Self::Done => item.done,
+        }
+    }
+
+    fn sql_clause(self) -> &'static str {
+        match self {
+            Self::All => "",
+            Self::Pending => " AND items.done = 0",
+            Self::Done => " AND items.done = 1",
+        }
+    }
+}
+
+/// Listing order. Every order is made total by falling back to `id`,
+/// which keeps keyset pagination stable across equal keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemSort {
+    /// User-defined order, changed by drag-and-drop
+    #[default]
+    Manual,
+    /// Creation order
+    Oldest,
+    Newest,
+    /// Most recently updated first
+    Updated,
+    /// Alphabetical, case-insensitive
+    Title,
+}
+
+impl ItemSort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Oldest => "oldest",
+            Self::Newest => "newest",
+            Self::Updated => "updated",
+            Self::Title => "title",
+        }
+    }
+
+    fn compare(self, a: &Item, b: &Item) -> Ordering {
+        match self {
+            Self::Manual => (a.position, a.id).cmp(&(b.position, b.id)),
+            Self::Oldest => (a.created_at, a.id).cmp(&(b.created_at, b.id)),
+            Self::Newest => (b.created_at, b.id).cmp(&(a.created_at, a.id)),
+            Self::Updated => (b.updated_at, b.id).cmp(&(a.updated_at, a.id)),
+            Self::Title => (a.title.to_lowercase(), a.id).cmp(&(b.title.to_lowercase(), b.id)),
+        }
+    }
+
+    /// Sort key expression and whether it runs descending
+    fn sql_key(self) -> (&'static str, bool) {
+        match self {
+            Self::Manual => ("items.position", false),
+            Self::Oldest => ("items.created_at", false),
+            Self::Newest => ("items.created_at", true),
+            Self::Updated => ("items.updated_at", true),
+            Self::Title => ("items.title COLLATE NOCASE", false),
+        }
+    }
+}
+
+/// Filter and sort options for a paginated item listing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemQuery {
+    #[serde(default)]
+    pub status: StatusFilter,
+    #[serde(default)]
+    pub sort: ItemSort,
+    /// Only items carrying this tag; an empty `tag=` means any tag
+    #[serde(default, deserialize_with = "empty_as_none")]
+    pub tag: Option<String>,
+    /// Only items without a parent — the item list, where subtasks are
+    /// loaded under their parent instead. Not part of the URL.
+    #[serde(skip)]
+    pub top_level: bool,
+}
+
+impl ItemQuery {
+    /// URL query string form, e.g. `status=pending&sort=newest&tag=urgent`
+    pub fn to_query_string(&self) -> String {
+        let mut query = format!(
+            "status={}&sort={}",
+            self.status.as_str(),
+            self.sort.as_str()
+        );
+        if let Some(tag) = &self.tag {
+            // Tag names are URL-safe by construction (see `normalize_tag`)
+            query.push_str("&tag=");
+            query.push_str(tag);
+        }
+        query
+    }
+}
+
+/// Deserialize a blank form/query value as `None`
+pub(crate) fn empty_as_none<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let value = Option::<String>::deserialize(deserializer)?;
+    Ok(value.filter(|v| !v.trim().is_empty()))
 }
 
-/// Item service trait — defines operations for item management
+/// One page of a keyset-paginated item listing
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemPage {
+    pub items: Vec<Item>,
+    /// Cursor for the following page (id of the last item), `None` when exhausted
+    pub next_cursor: Option<u32>,
+}
+
+impl ItemPage {
+    /// Build a page from up to `limit + 1` rows — the extra row only signals
+    /// that another page exists and is dropped.
+    fn from_rows(mut items: Vec<Item>, limit: u32) -> Self {
+        let has_more = items.len() > limit as usize;
+        items.truncate(limit as usize);
+        let next_cursor = if has_more {
+            items.last().map(|i| i.id)
+        } else {
+            None
+        };
+        Self { items, next_cursor }
+    }
+}
+
+/// Item service trait — defines operations for item management.
+///
+/// Every operation is fallible: storage failures surface as
+/// `AppError::Database` and missing items as `AppError::NotFound`.
+/// Writes that take an `actor` record who made them in the item's history,
+/// atomically with the change itself.
+#[async_trait]
 pub trait ItemService: Send + Sync {
-    fn list_all(&self) -> Vec<Item>;
-    fn get_by_id(&self, id: u32) -> Option<Item>;
-    fn create(&self, title: String, description: String) -> Item;
-    fn toggle_done(&self, id: u32) -> Option<Item>;
-    fn delete(&self, id: u32) -> bool;
+    /// Live items in manual order
+    async fn list_all(&self) -> AppResult<Vec<Item>>;
+    /// Keyset pagination in `query` order: at most `limit` items following
+    /// the item with id `after` (from the start when `None`)
+    async fn list_page(
+        &self,
+        query: &ItemQuery,
+        after: Option<u32>,
+        limit: u32,
+    ) -> AppResult<ItemPage>;
+    /// Full-text search over title and description, best matches first.
+    /// Every term must match the start of a word; an empty query matches nothing.
+    async fn search(&self, query: &str, limit: u32) -> AppResult<Vec<Item>>;
+    async fn get_by_id(&self, id: u32) -> AppResult<Item>;
+    /// Create a live item, last in manual order. Invalid tags or a parent
+    /// that isn't a live item fail with `AppError::Validation`.
+    async fn create(&self, changes: ItemChanges, actor: &str) -> AppResult<Item>;
+    /// Create many items at once, appended in the order given. All-or-nothing
+    /// (one transaction in SQLite); returns how many were created. A parent
+    /// index outside the batch fails with `AppError::Validation`; callers
+    /// make sure parents don't form a loop.
+    async fn import(&self, items: Vec<NewItem>, actor: &str) -> AppResult<usize>;
+    /// Replace everything the form edits at once — one version bump, one
+    /// history entry. The new parent is checked first, as for `create`
+    /// (and it can't be the item itself or one of its subtasks); then the
+    /// item must still be at `expected_version`, otherwise this fails with
+    /// `AppError::Conflict`.
+    async fn update(
+        &self,
+        id: u32,
+        changes: ItemChanges,
+        expected_version: u32,
+        actor: &str,
+    ) -> AppResult<Item>;
+    async fn toggle_done(&self, id: u32, actor: &str) -> AppResult<Item>;
+    /// Put the given live items in the given order, within the slots they
+    /// already occupy — items not listed keep their place. All positions are
+    /// renumbered atomically. Only `position` changes: order is not content,
+    /// so `version` and `updated_at` stay as they are.
+    async fn reorder(&self, ids: &[u32]) -> AppResult<()>;
+    /// Replace a live item's tags. Names are normalized; invalid names
+    /// fail with `AppError::Validation`.
+    async fn set_tags(&self, id: u32, tags: Vec<String>, actor: &str) -> AppResult<Item>;
+    /// Set or clear a live item's due date
+    async fn set_due(&self, id: u32, due_at: Option<DateTime<Utc>>, actor: &str)
+        -> AppResult<Item>;
+    /// Live, pending items due at or before `until` that haven't had a
+    /// reminder for their current due date yet, soonest first
+    async fn due_for_reminder(&self, until: DateTime<Utc>) -> AppResult<Vec<Item>>;
+    /// Note that a reminder went out for the item's `due_at` — a no-op if
+    /// the due date has changed since
+    async fn mark_reminded(&self, id: u32, due_at: DateTime<Utc>) -> AppResult<()>;
+    /// Tags carried by live items, alphabetical
+    async fn list_tags(&self) -> AppResult<Vec<Tag>>;
+    /// Remove a tag from every item, recording the change on each
+    async fn delete_tag(&self, name: &str, actor: &str) -> AppResult<()>;
+    /// Move a live item, with its live subtasks, to the trash. Trashed items
+    /// are hidden from every other query until restored.
+    async fn delete(&self, id: u32, actor: &str) -> AppResult<Item>;
+
+    // Batch operations run all-or-nothing (one transaction in SQLite): if any
+    // id is missing or trashed the batch fails with `AppError::NotFound` and
+    // nothing changes. Each returns how many items actually changed.
+
+    /// Mark the given items done (or pending)
+    async fn bulk_set_done(&self, ids: &[u32], done: bool, actor: &str) -> AppResult<usize>;
+    /// Move the given items (and their subtasks) to the trash
+    async fn bulk_delete(&self, ids: &[u32], actor: &str) -> AppResult<usize>;
+    /// Add one tag to each of the given items
+    async fn bulk_add_tag(&self, ids: &[u32], tag: &str, actor: &str) -> AppResult<usize>;
+    /// Trashed items, most recently deleted first — except subtasks that
+    /// were trashed together with their parent, which come and go with it
+    async fn list_trash(&self) -> AppResult<Vec<Item>>;
+    /// Bring a trashed item back to the live list, with the subtasks trashed
+    /// along with it. If its parent is still in the trash it comes back as a
+    /// top-level item.
+    async fn restore(&self, id: u32, actor: &str) -> AppResult<Item>;
+    /// Permanently remove a trashed item and all its subtasks — live items
+    /// must be deleted first. Their history goes with them.
+    async fn purge(&self, id: u32) -> AppResult<()>;
+    /// Recorded changes to a live or trashed item, newest first
+    async fn history(&self, id: u32) -> AppResult<Vec<ItemEvent>>;
+
+    // Subtasks: any item can have child items, to any depth. Trashing an
+    // item trashes its live subtasks with it, and restoring it brings back
+    // the ones trashed together with it. Purging removes the whole subtree.
+
+    /// Live subtasks of a live item, in manual order
+    async fn list_children(&self, parent_id: u32) -> AppResult<Vec<Item>>;
+    /// Create a subtask of a live item, last in manual order
+    async fn create_child(&self, parent_id: u32, title: String, actor: &str) -> AppResult<Item>;
+    /// Make a live item a subtask of another live item, or top-level with
+    /// `None`. Moving an item below itself or one of its own subtasks fails
+    /// with `AppError::Validation`.
+    async fn move_to(&self, id: u32, parent_id: Option<u32>, actor: &str) -> AppResult<Item>;
+    /// Ids of an item and all its descendants, live or trashed — everything
+    /// a purge of it removes
+    async fn subtree(&self, id: u32) -> AppResult<Vec<u32>>;
+}
+
+fn item_not_found(id: u32) -> AppError {
+    AppError::not_found(format!("Item {}", id))
+}
+
+fn item_conflict(id: u32) -> AppError {
+    AppError::conflict(format!("Item {} was changed by someone else", id))
+}
+
+fn move_cycle(id: u32) -> AppError {
+    AppError::validation(format!(
+        "Item {} can't become a subtask of itself or of its own subtasks",
+        id
+    ))
+}
+
+fn parent_not_found(id: u32) -> AppError {
+    AppError::validation(format!("There is no item #{}", id))
+}
+
+fn tag_not_found(name: &str) -> AppError {
+    AppError::not_found(format!("Tag {}", name))
+}
+
+/// Parent indices of an import batch must point into the batch
+fn check_import_parents(items: &[NewItem]) -> AppResult<()> {
+    match items
+        .iter()
+        .filter_map(|new| new.parent)
+        .find(|&index| index >= items.len())
+    {
+        Some(index) => Err(AppError::validation(format!(
+            "Imported parent #{} is not part of the import",
+            index + 1
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Full item order after a reorder: `current` is every item as
+/// `(id, is_live)` in position order, and the items in `ids` are moved into
+/// the slots they occupy, in the sequence given.
+fn reordered(current: &[(u32, bool)], ids: &[u32]) -> AppResult<Vec<u32>> {
+    let moved: HashSet<u32> = ids.iter().copied().collect();
+    if moved.len() != ids.len() {
+        return Err(AppError::validation("Each item may appear only once"));
+    }
+    if let Some(&missing) = ids
+        .iter()
+        .find(|id| !current.iter().any(|&(cur, live)| cur == **id && live))
+    {
+        return Err(item_not_found(missing));
+    }
+
+    let mut next = ids.iter();
+    Ok(current
+        .iter()
+        .map(|&(id, _)| {
+            if moved.contains(&id) {
+                *next.next().expect("one slot per moved id")
+            } else {
+                id
+            }
+        })
+        .collect())
+}
+
+/// Batch ids without duplicates, in first-seen order
+fn unique_ids(ids: &[u32]) -> Vec<u32> {
+    let mut seen = HashSet::new();
+    ids.iter().copied().filter(|id| seen.insert(*id)).collect()
+}
+
+fn too_many_tags(id: u32) -> AppError {
+    AppError::validation(format!(
+        "Item {} already has {} tags",
+        id, MAX_TAGS_PER_ITEM
+    ))
+}
+
+/// Service-side validation of tag names supplied by callers
+fn checked_tags(tags: &[String]) -> AppResult<Vec<String>> {
+    normalize_tags(tags.iter().map(String::as_str)).map_err(AppError::validation)
 }
 
 /// In-memory item storage (good for prototyping, tests)
 pub struct InMemoryItemService {
     items: RwLock<Vec<Item>>,
     next_id: RwLock<u32>,
+    events: RwLock<Vec<ItemEvent>>,
+    /// Due date each item was last reminded about
+    reminded: RwLock<HashMap<u32, DateTime<Utc>>>,
 }
 
 impl InMemoryItemService {
     pub fn new() -> Self {
         // Seed with example data
-        let items = vec![
-            Item {
-                id: 1,
-                title: "Set up project".into(),
-                description: "Scaffold Axum + HTMX boilerplate".into(),
-                done: true,
-            },
-            Item {
-                id: 2,
-                title: "Add database".into(),
-                description: "Integrate SQLite or Postgres".into(),
-                done: false,
-            },
-            Item {
-                id: 3,
-                title: "Deploy".into(),
-                description: "Containerize and ship to production".into(),
-                done: false,
-            },
+        let mut items = vec![
+            Self::new_item(1, "Set up project", "Scaffold Axum + HTMX boilerplate"),
+            Self::new_item(2, "Add database", "Integrate SQLite or Postgres"),
+            Self::new_item(3, "Deploy", "Containerize and ship to production"),
         ];
+        items[0].done = true;
 
         Self {
             items: RwLock::new(items),
             next_id: RwLock::new(4),
+            events: RwLock::new(Vec::new()),
+            reminded: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn record(&self, item_id: u32, kind: ItemEventKind, actor: &str, values: EventValues) {
+        let mut events = self.events.write().unwrap();
+        let id = events.len() as u32 + 1;
+        events.push(ItemEvent {
+            id,
+            item_id,
+            kind,
+            actor: actor.to_string(),
+            at: Utc::now(),
+            changes: values.changes(),
+        });
+    }
+
+    /// Trash an item and its live descendants, recording each
+    fn trash_subtree(&self, items: &mut [Item], id: u32, now: DateTime<Utc>, actor: &str) {
+        for sub in subtree_ids(items, id) {
+            let item = items.iter_mut().find(|i| i.id == sub).unwrap();
+            if item.deleted_at.is_none() {
+                item.deleted_at = Some(now);
+                item.version += 1;
+                self.record(sub, ItemEventKind::Deleted, actor, EventValues::default());
+            }
+        }
+    }
+
+    fn new_item(id: u32, title: impl Into<String>, description: impl Into<String>) -> Item {
+        let now = Utc::now();
+        Item {
+            id,
+            title: title.into(),
+            description: description.into(),
+            done: false,
+            version: 1,
+            tags: Vec::new(),
+            position: id,
+            parent_id: None,
+            subtasks: 0,
+            subtasks_done: 0,
+            due_at: None,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        }
+    }
+}
+
+/// Find a live (not trashed) item for mutation
+fn find_live(items: &mut [Item], id: u32) -> AppResult<&mut Item> {
+    items
+        .iter_mut()
+        .find(|i| i.id == id && i.deleted_at.is_none())
+        .ok_or_else(|| item_not_found(id))
+}
+
+/// Fail with `Validation` unless `parent_id` names a live item that is
+/// neither `id` nor one of its subtasks
+fn check_parent_in(items: &[Item], id: Option<u32>, parent_id: u32) -> AppResult<()> {
+    if !items
+        .iter()
+        .any(|i| i.id == parent_id && i.deleted_at.is_none())
+    {
+        return Err(parent_not_found(parent_id));
+    }
+    match id {
+        Some(id) if subtree_ids(items, id).contains(&parent_id) => Err(move_cycle(id)),
+        _ => Ok(()),
+    }
+}
+
+/// Refresh every item's subtask counts after a write
+fn recount(items: &mut [Item]) {
+    let mut counts: HashMap<u32, (u32, u32)> = HashMap::new();
+    for item in items.iter().filter(|i| i.deleted_at.is_none()) {
+        if let Some(parent_id) = item.parent_id {
+            let (total, done) = counts.entry(parent_id).or_default();
+            *total += 1;
+            *done += item.done as u32;
         }
     }
+    for item in items.iter_mut() {
+        (item.subtasks, item.subtasks_done) = counts.get(&item.id).copied().unwrap_or_default();
+    }
+}
+
+/// Ids of an item and all its descendants, parents before their children
+fn subtree_ids(items: &[Item], id: u32) -> Vec<u32> {
+    let mut ids = vec![id];
+    let mut next = 0;
+    while let Some(&parent_id) = ids.get(next) {
+        ids.extend(
+            items
+                .iter()
+                .filter(|i| i.parent_id == Some(parent_id))
+                .map(|i| i.id),
+        );
+        next += 1;
+    }
+    ids
+}
+
+fn cloned(items: &[Item], id: u32) -> Item {
+    items
+        .iter()
+        .find(|i| i.id == id)
+        .cloned()
+        .expect("item exists")
 }
 
 impl Default for InMemoryItemService {
@@ -67,172 +830,2169 @@ impl Default for InMemoryItemService {
     }
 }
 
+#[async_trait]
 impl ItemService for InMemoryItemService {
-    fn list_all(&self) -> Vec<Item> {
-        self.items.read().unwrap().clone()
+    async fn list_all(&self) -> AppResult<Vec<Item>> {
+        let mut items: Vec<Item> = self
+            .items
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|i| i.deleted_at.is_none())
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| ItemSort::Manual.compare(a, b));
+        Ok(items)
+    }
+
+    async fn list_page(
+        &self,
+        query: &ItemQuery,
+        after: Option<u32>,
+        limit: u32,
+    ) -> AppResult<ItemPage> {
+        let mut items: Vec<Item> = self
+            .items
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|i| i.deleted_at.is_none() && query.status.matches(i))
+            .filter(|i| !query.top_level || i.parent_id.is_none())
+            .filter(|i| query.tag.as_ref().is_none_or(|tag| i.tags.contains(tag)))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| query.sort.compare(a, b));
+
+        // Resume just past the cursor item; a cursor that vanished ends the listing
+        let start = match after {
+            Some(id) => items
+                .iter()
+                .position(|i| i.id == id)
+                .map_or(items.len(), |p| p + 1),
+            None => 0,
+        };
+        let items = items
+            .into_iter()
+            .skip(start)
+            .take(limit as usize + 1)
+            .collect();
+        Ok(ItemPage::from_rows(items, limit))
+    }
+
+    async fn search(&self, query: &str, limit: u32) -> AppResult<Vec<Item>> {
+        // Naive fallback for FTS5: every term must prefix some word
+        let terms = search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let items = self.items.read().unwrap();
+        Ok(items
+            .iter()
+            .filter(|item| item.deleted_at.is_none())
+            .filter(|item| {
+                let text = format!("{} {}", item.title, item.description).to_lowercase();
+                terms.iter().all(|term| {
+                    text.split(|c: char| !c.is_alphanumeric())
+                        .any(|word| word.starts_with(term.as_str()))
+                })
+            })
+            .take(limit as usize)
+            .cloned()
+            .collect())
     }
 
-    fn get_by_id(&self, id: u32) -> Option<Item> {
+    async fn get_by_id(&self, id: u32) -> AppResult<Item> {
         self.items
             .read()
             .unwrap()
             .iter()
-            .find(|i| i.id == id)
+            .find(|i| i.id == id && i.deleted_at.is_none())
             .cloned()
+            .ok_or_else(|| item_not_found(id))
     }
 
-    fn create(&self, title: String, description: String) -> Item {
+    async fn create(&self, changes: ItemChanges, actor: &str) -> AppResult<Item> {
+        let tags = checked_tags(&changes.tags)?;
         let mut next_id = self.next_id.write().unwrap();
-        let item = Item {
-            id: *next_id,
-            title,
-            description,
-            done: false,
-        };
+        let mut items = self.items.write().unwrap();
+        if let Some(parent_id) = changes.parent_id {
+            check_parent_in(&items, None, parent_id)?;
+        }
+        let mut item = Self::new_item(*next_id, changes.title, changes.description);
+        item.position = items.iter().map(|i| i.position).max().unwrap_or(0) + 1;
+        item.tags = tags;
+        item.due_at = changes.due_at;
+        item.parent_id = changes.parent_id;
         *next_id += 1;
 
-        self.items.write().unwrap().push(item.clone());
-        item
+        self.record(
+            item.id,
+            ItemEventKind::Created,
+            actor,
+            EventValues::created(&item),
+        );
+        let id = item.id;
+        items.push(item);
+        recount(&mut items);
+        Ok(cloned(&items, id))
     }
 
-    fn toggle_done(&self, id: u32) -> Option<Item> {
+    async fn import(&self, new_items: Vec<NewItem>, actor: &str) -> AppResult<usize> {
+        let tag_sets = new_items
+            .iter()
+            .map(|new| checked_tags(&new.tags))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        check_import_parents(&new_items)?;
+
+        let mut next_id = self.next_id.write().unwrap();
         let mut items = self.items.write().unwrap();
-        if let Some(item) = items.iter_mut().find(|i| i.id == id) {
-            item.done = !item.done;
-            Some(item.clone())
-        } else {
-            None
+        let mut position = items.iter().map(|i| i.position).max().unwrap_or(0);
+        let first_id = *next_id;
+        let count = new_items.len();
+        for (new, tags) in new_items.into_iter().zip(tag_sets) {
+            let mut item = Self::new_item(*next_id, new.title, new.description);
+            position += 1;
+            item.position = position;
+            item.done = new.done;
+            item.tags = tags;
+            item.due_at = new.due_at;
+            item.parent_id = new.parent.map(|index| first_id + index as u32);
+            *next_id += 1;
+            self.record(
+                item.id,
+                ItemEventKind::Created,
+                actor,
+                EventValues::created(&item),
+            );
+            items.push(item);
         }
+        recount(&mut items);
+        Ok(count)
     }
 
-    fn delete(&self, id: u32) -> bool {
+    async fn update(
+        &self,
+        id: u32,
+        changes: ItemChanges,
+        expected_version: u32,
+        actor: &str,
+    ) -> AppResult<Item> {
+        let tags = checked_tags(&changes.tags)?;
         let mut items = self.items.write().unwrap();
-        let len_before = items.len();
-        items.retain(|i| i.id != id);
-        items.len() < len_before
-    }
-}
+        find_live(&mut items, id)?;
+        if let Some(parent_id) = changes.parent_id {
+            check_parent_in(&items, Some(id), parent_id)?;
+        }
+        let item = find_live(&mut items, id)?;
+        if item.version != expected_version {
+            return Err(item_conflict(id));
+        }
+        let old = item.clone();
+        item.title = changes.title;
+        item.description = changes.description;
+        item.tags = tags;
+        item.due_at = changes.due_at;
+        item.parent_id = changes.parent_id;
+        item.version += 1;
+        item.updated_at = Utc::now();
 
-// ============================================================================
-// SQLx Implementation — SQLite-backed item storage
-// ============================================================================
+        let values = EventValues::between(&old, item);
+        if !values.is_empty() {
+            self.record(id, ItemEventKind::Updated, actor, values);
+        }
+        recount(&mut items);
+        Ok(cloned(&items, id))
+    }
 
-use sqlx::sqlite::SqlitePool;
+    async fn toggle_done(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut items = self.items.write().unwrap();
+        let item = find_live(&mut items, id)?;
+        item.done = !item.done;
+        item.version += 1;
+        item.updated_at = Utc::now();
+        self.record(
+            id,
+            ItemEventKind::Toggled,
+            actor,
+            EventValues::done(item.done),
+        );
+        recount(&mut items);
+        Ok(cloned(&items, id))
+    }
 
-pub struct SqliteItemService {
-    pool: SqlitePool,
-}
+    async fn reorder(&self, ids: &[u32]) -> AppResult<()> {
+        let mut items = self.items.write().unwrap();
+        items.sort_by(|a, b| ItemSort::Manual.compare(a, b));
+        let current: Vec<(u32, bool)> = items
+            .iter()
+            .map(|i| (i.id, i.deleted_at.is_none()))
+            .collect();
+        let order = reordered(&current, ids)?;
 
-impl SqliteItemService {
-    pub fn new(pool: SqlitePool) -> Self {
-        Self { pool }
+        for (position, id) in (1..).zip(order) {
+            let item = items.iter_mut().find(|i| i.id == id).unwrap();
+            item.position = position;
+        }
+        Ok(())
     }
-}
 
-/// Row type returned by SQLx queries (SQLite stores booleans as integers)
-#[derive(sqlx::FromRow)]
-struct ItemRow {
-    id: i64,
-    title: String,
-    description: String,
-    done: i32,
-}
+    async fn set_tags(&self, id: u32, tags: Vec<String>, actor: &str) -> AppResult<Item> {
+        let tags = checked_tags(&tags)?;
+        let mut items = self.items.write().unwrap();
+        let item = find_live(&mut items, id)?;
+        // Resubmitting the same (normalized) set is not an edit
+        if item.tags == tags {
+            return Ok(item.clone());
+        }
+        let old = item.clone();
+        item.tags = tags;
+        item.version += 1;
+        item.updated_at = Utc::now();
 
-impl From<ItemRow> for Item {
-    fn from(row: ItemRow) -> Self {
-        Item {
-            id: row.id as u32,
-            title: row.title,
-            description: row.description,
-            done: row.done != 0,
+        let values = EventValues::between(&old, item);
+        if !values.is_empty() {
+            self.record(id, ItemEventKind::Updated, actor, values);
         }
+        Ok(item.clone())
     }
-}
 
-impl ItemService for SqliteItemService {
-    fn list_all(&self) -> Vec<Item> {
-        // Block on async query from sync trait — runs on the tokio runtime
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                sqlx::query_as::<_, ItemRow>(
-                    "SELECT id, title, description, done FROM items ORDER BY id",
-                )
-                .fetch_all(&self.pool)
-                .await
-                .unwrap_or_default()
-                .into_iter()
-                .map(Item::from)
-                .collect()
+    async fn set_due(
+        &self,
+        id: u32,
+        due_at: Option<DateTime<Utc>>,
+        actor: &str,
+    ) -> AppResult<Item> {
+        let mut items = self.items.write().unwrap();
+        let item = find_live(&mut items, id)?;
+        let old = item.clone();
+        item.due_at = due_at;
+        item.version += 1;
+        item.updated_at = Utc::now();
+        self.record(
+            id,
+            ItemEventKind::Updated,
+            actor,
+            EventValues::between(&old, item),
+        );
+        Ok(item.clone())
+    }
+
+    async fn due_for_reminder(&self, until: DateTime<Utc>) -> AppResult<Vec<Item>> {
+        let reminded = self.reminded.read().unwrap();
+        let mut due: Vec<Item> = self
+            .items
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|i| i.deleted_at.is_none() && !i.done)
+            .filter(|i| {
+                i.due_at
+                    .is_some_and(|at| at <= until && reminded.get(&i.id) != Some(&at))
             })
-        })
+            .cloned()
+            .collect();
+        due.sort_by_key(|i| (i.due_at, i.id));
+        Ok(due)
     }
 
-    fn get_by_id(&self, id: u32) -> Option<Item> {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                sqlx::query_as::<_, ItemRow>(
-                    "SELECT id, title, description, done FROM items WHERE id = ?",
-                )
+    async fn mark_reminded(&self, id: u32, due_at: DateTime<Utc>) -> AppResult<()> {
+        let items = self.items.read().unwrap();
+        if items.iter().any(|i| i.id == id && i.due_at == Some(due_at)) {
+            self.reminded.write().unwrap().insert(id, due_at);
+        }
+        Ok(())
+    }
+
+    async fn list_tags(&self) -> AppResult<Vec<Tag>> {
+        let mut counts: BTreeMap<String, u32> = BTreeMap::new();
+        for item in self.items.read().unwrap().iter() {
+            if item.deleted_at.is_none() {
+                for tag in &item.tags {
+                    *counts.entry(tag.clone()).or_default() += 1;
+                }
+            }
+        }
+        Ok(counts
+            .into_iter()
+            .map(|(name, item_count)| Tag { name, item_count })
+            .collect())
+    }
+
+    async fn delete_tag(&self, name: &str, actor: &str) -> AppResult<()> {
+        let mut items = self.items.write().unwrap();
+        let mut found = false;
+        for item in items
+            .iter_mut()
+            .filter(|i| i.tags.iter().any(|t| t == name))
+        {
+            let old = item.tags.clone();
+            item.tags.retain(|t| t != name);
+            item.version += 1;
+            item.updated_at = Utc::now();
+            found = true;
+            let values = EventValues::tags(&old, &item.tags);
+            self.record(item.id, ItemEventKind::Updated, actor, values);
+        }
+        if found {
+            Ok(())
+        } else {
+            Err(tag_not_found(name))
+        }
+    }
+
+    async fn bulk_set_done(&self, ids: &[u32], done: bool, actor: &str) -> AppResult<usize> {
+        let mut items = self.items.write().unwrap();
+        let ids = unique_ids(ids);
+        for &id in &ids {
+            find_live(&mut items, id)?;
+        }
+
+        let now = Utc::now();
+        let mut changed = 0;
+        for &id in &ids {
+            let item = find_live(&mut items, id)?;
+            if item.done != done {
+                item.done = done;
+                item.version += 1;
+                item.updated_at = now;
+                changed += 1;
+                self.record(id, ItemEventKind::Toggled, actor, EventValues::done(done));
+            }
+        }
+        recount(&mut items);
+        Ok(changed)
+    }
+
+    async fn bulk_delete(&self, ids: &[u32], actor: &str) -> AppResult<usize> {
+        let mut items = self.items.write().unwrap();
+        let ids = unique_ids(ids);
+        for &id in &ids {
+            find_live(&mut items, id)?;
+        }
+
+        let now = Utc::now();
+        for &id in &ids {
+            self.trash_subtree(&mut items, id, now, actor);
+        }
+        recount(&mut items);
+        Ok(ids.len())
+    }
+
+    async fn bulk_add_tag(&self, ids: &[u32], tag: &str, actor: &str) -> AppResult<usize> {
+        let tag = normalize_tag(tag).map_err(AppError::validation)?;
+        let mut items = self.items.write().unwrap();
+        let ids = unique_ids(ids);
+        for &id in &ids {
+            let item = find_live(&mut items, id)?;
+            if !item.tags.contains(&tag) && item.tags.len() >= MAX_TAGS_PER_ITEM {
+                return Err(too_many_tags(id));
+            }
+        }
+
+        let now = Utc::now();
+        let mut changed = 0;
+        for &id in &ids {
+            let item = find_live(&mut items, id)?;
+            if !item.tags.contains(&tag) {
+                let old = item.tags.clone();
+                item.tags.push(tag.clone());
+                item.tags.sort();
+                item.version += 1;
+                item.updated_at = now;
+                changed += 1;
+                let values = EventValues::tags(&old, &item.tags);
+                self.record(id, ItemEventKind::Updated, actor, values);
+            }
+        }
+        Ok(changed)
+    }
+
+    async fn delete(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut items = self.items.write().unwrap();
+        find_live(&mut items, id)?;
+        self.trash_subtree(&mut items, id, Utc::now(), actor);
+        recount(&mut items);
+        Ok(cloned(&items, id))
+    }
+
+    async fn list_trash(&self) -> AppResult<Vec<Item>> {
+        let items = self.items.read().unwrap();
+        let trashed_with_parent = |item: &Item| {
+            items
+                .iter()
+                .any(|p| Some(p.id) == item.parent_id && p.deleted_at == item.deleted_at)
+        };
+        let mut trash: Vec<Item> = items
+            .iter()
+            .filter(|i| i.deleted_at.is_some() && !trashed_with_parent(i))
+            .cloned()
+            .collect();
+        trash.sort_by_key(|i| std::cmp::Reverse(i.deleted_at));
+        Ok(trash)
+    }
+
+    async fn restore(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut items = self.items.write().unwrap();
+        let item = items
+            .iter()
+            .find(|i| i.id == id && i.deleted_at.is_some())
+            .ok_or_else(|| item_not_found(id))?;
+        let deleted_at = item.deleted_at;
+        let parent_live = item
+            .parent_id
+            .is_none_or(|p| items.iter().any(|i| i.id == p && i.deleted_at.is_none()));
+
+        for sub in subtree_ids(&items, id) {
+            let item = items.iter_mut().find(|i| i.id == sub).unwrap();
+            if item.deleted_at != deleted_at {
+                continue;
+            }
+            let old = item.clone();
+            item.deleted_at = None;
+            item.version += 1;
+            if sub == id && !parent_live {
+                item.parent_id = None;
+            }
+            let values = EventValues::between(&old, item);
+            self.record(sub, ItemEventKind::Restored, actor, values);
+        }
+        recount(&mut items);
+        Ok(cloned(&items, id))
+    }
+
+    async fn purge(&self, id: u32) -> AppResult<()> {
+        let mut items = self.items.write().unwrap();
+        if !items.iter().any(|i| i.id == id && i.deleted_at.is_some()) {
+            return Err(item_not_found(id));
+        }
+        let purged = subtree_ids(&items, id);
+        items.retain(|i| !purged.contains(&i.id));
+        self.events
+            .write()
+            .unwrap()
+            .retain(|e| !purged.contains(&e.item_id));
+        recount(&mut items);
+        Ok(())
+    }
+
+    async fn history(&self, id: u32) -> AppResult<Vec<ItemEvent>> {
+        if !self.items.read().unwrap().iter().any(|i| i.id == id) {
+            return Err(item_not_found(id));
+        }
+        Ok(self
+            .events
+            .read()
+            .unwrap()
+            .iter()
+            .rev()
+            .filter(|e| e.item_id == id)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_children(&self, parent_id: u32) -> AppResult<Vec<Item>> {
+        self.get_by_id(parent_id).await?;
+        let mut children: Vec<Item> = self
+            .items
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|i| i.parent_id == Some(parent_id) && i.deleted_at.is_none())
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| ItemSort::Manual.compare(a, b));
+        Ok(children)
+    }
+
+    async fn create_child(&self, parent_id: u32, title: String, actor: &str) -> AppResult<Item> {
+        let mut next_id = self.next_id.write().unwrap();
+        let mut items = self.items.write().unwrap();
+        find_live(&mut items, parent_id)?;
+        let mut item = Self::new_item(*next_id, title, "");
+        item.position = items.iter().map(|i| i.position).max().unwrap_or(0) + 1;
+        item.parent_id = Some(parent_id);
+        *next_id += 1;
+
+        self.record(
+            item.id,
+            ItemEventKind::Created,
+            actor,
+            EventValues::created(&item),
+        );
+        let id = item.id;
+        items.push(item);
+        recount(&mut items);
+        Ok(cloned(&items, id))
+    }
+
+    async fn move_to(&self, id: u32, parent_id: Option<u32>, actor: &str) -> AppResult<Item> {
+        let mut items = self.items.write().unwrap();
+        find_live(&mut items, id)?;
+        if let Some(parent_id) = parent_id {
+            find_live(&mut items, parent_id)?;
+            if subtree_ids(&items, id).contains(&parent_id) {
+                return Err(move_cycle(id));
+            }
+        }
+
+        let item = find_live(&mut items, id)?;
+        if item.parent_id != parent_id {
+            let old = item.clone();
+            item.parent_id = parent_id;
+            item.version += 1;
+            item.updated_at = Utc::now();
+            let values = EventValues::between(&old, item);
+            self.record(id, ItemEventKind::Updated, actor, values);
+            recount(&mut items);
+        }
+        Ok(cloned(&items, id))
+    }
+
+    async fn subtree(&self, id: u32) -> AppResult<Vec<u32>> {
+        let items = self.items.read().unwrap();
+        if !items.iter().any(|i| i.id == id) {
+            return Err(item_not_found(id));
+        }
+        Ok(subtree_ids(&items, id))
+    }
+}
+
+// ============================================================================
+// SQLx Implementation — SQLite-backed item storage
+// ============================================================================
+
+use sqlx::sqlite::SqlitePool;
+
+pub struct SqliteItemService {
+    pool: SqlitePool,
+}
+
+impl SqliteItemService {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+/// Columns selected into `ItemRow` — qualified so they also work in joins
+/// (tags are aggregated into one comma-separated column, and live subtasks
+/// are counted for the progress rollup)
+const ITEM_COLUMNS: &str = "items.id, items.title, items.description, items.done, items.version, \
+     items.position, items.parent_id, items.due_at, items.created_at, items.updated_at, \
+     items.deleted_at, \
+     (SELECT group_concat(name, ',') FROM (SELECT tags.name AS name FROM item_tags \
+      JOIN tags ON tags.id = item_tags.tag_id \
+      WHERE item_tags.item_id = items.id ORDER BY tags.name)) AS tags, \
+     (SELECT COUNT(*) FROM items AS sub \
+      WHERE sub.parent_id = items.id AND sub.deleted_at IS NULL) AS subtasks, \
+     (SELECT COUNT(*) FROM items AS sub \
+      WHERE sub.parent_id = items.id AND sub.deleted_at IS NULL AND sub.done != 0) AS subtasks_done";
+
+/// Names an item (bind its id first) and all its descendants as `subtree`
+const SUBTREE_CTE: &str = "WITH RECURSIVE subtree (id) AS (SELECT ? UNION \
+     SELECT items.id FROM items JOIN subtree ON items.parent_id = subtree.id)";
+
+/// Drops tags no item refers to any more
+const DELETE_ORPHAN_TAGS: &str =
+    "DELETE FROM tags WHERE id NOT IN (SELECT DISTINCT tag_id FROM item_tags)";
+
+/// Row type returned by SQLx queries (SQLite stores booleans as integers)
+#[derive(sqlx::FromRow)]
+struct ItemRow {
+    id: i64,
+    title: String,
+    description: String,
+    done: i32,
+    version: i64,
+    position: i64,
+    parent_id: Option<i64>,
+    due_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+    deleted_at: Option<DateTime<Utc>>,
+    tags: Option<String>,
+    subtasks: i64,
+    subtasks_done: i64,
+}
+
+impl From<ItemRow> for Item {
+    fn from(row: ItemRow) -> Self {
+        Item {
+            id: row.id as u32,
+            title: row.title,
+            description: row.description,
+            done: row.done != 0,
+            version: row.version as u32,
+            tags: row
+                .tags
+                .map(|tags| tags.split(',').map(String::from).collect())
+                .unwrap_or_default(),
+            position: row.position as u32,
+            parent_id: row.parent_id.map(|id| id as u32),
+            subtasks: row.subtasks as u32,
+            subtasks_done: row.subtasks_done as u32,
+            due_at: row.due_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+            deleted_at: row.deleted_at,
+        }
+    }
+}
+
+/// Replace an item's tag links, creating any tags that don't exist yet
+async fn replace_tags(
+    conn: &mut sqlx::SqliteConnection,
+    id: u32,
+    tags: &[String],
+) -> AppResult<()> {
+    sqlx::query("DELETE FROM item_tags WHERE item_id = ?")
+        .bind(id as i64)
+        .execute(&mut *conn)
+        .await?;
+    for name in tags {
+        sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT (name) DO NOTHING")
+            .bind(name)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query(
+            "INSERT INTO item_tags (item_id, tag_id) SELECT ?, id FROM tags WHERE name = ?",
+        )
+        .bind(id as i64)
+        .bind(name)
+        .execute(&mut *conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Row type for `item_events`
+#[derive(sqlx::FromRow)]
+struct EventRow {
+    id: i64,
+    item_id: i64,
+    kind: String,
+    actor: String,
+    old_values: String,
+    new_values: String,
+    created_at: DateTime<Utc>,
+}
+
+impl TryFrom<EventRow> for ItemEvent {
+    type Error = AppError;
+
+    fn try_from(row: EventRow) -> AppResult<Self> {
+        let kind = ItemEventKind::parse(&row.kind)
+            .ok_or_else(|| AppError::internal(format!("Unknown item event kind {}", row.kind)))?;
+        let values = EventValues {
+            old: serde_json::from_str(&row.old_values).unwrap_or_default(),
+            new: serde_json::from_str(&row.new_values).unwrap_or_default(),
+        };
+        Ok(ItemEvent {
+            id: row.id as u32,
+            item_id: row.item_id as u32,
+            kind,
+            actor: row.actor,
+            at: row.created_at,
+            changes: values.changes(),
+        })
+    }
+}
+
+/// Append to an item's history on the writer's own connection, so the entry
+/// commits or rolls back together with the change it describes
+async fn record_event(
+    conn: &mut sqlx::SqliteConnection,
+    item_id: u32,
+    kind: ItemEventKind,
+    actor: &str,
+    values: EventValues,
+    at: DateTime<Utc>,
+) -> AppResult<()> {
+    sqlx::query(
+        "INSERT INTO item_events (item_id, kind, actor, old_values, new_values, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?)",
+    )
+    .bind(item_id as i64)
+    .bind(kind.as_str())
+    .bind(actor)
+    .bind(serde_json::Value::Object(values.old).to_string())
+    .bind(serde_json::Value::Object(values.new).to_string())
+    .bind(at)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Trash an item and its live descendants, recording each; returns their ids
+async fn trash_subtree(
+    conn: &mut sqlx::SqliteConnection,
+    id: u32,
+    now: DateTime<Utc>,
+    actor: &str,
+) -> AppResult<Vec<u32>> {
+    let trashed: Vec<i64> = sqlx::query_scalar(&format!(
+        "{SUBTREE_CTE} UPDATE items SET deleted_at = ?, version = version + 1 \
+         WHERE id IN (SELECT id FROM subtree) AND deleted_at IS NULL RETURNING id"
+    ))
+    .bind(id as i64)
+    .bind(now)
+    .fetch_all(&mut *conn)
+    .await?;
+    for &sub in &trashed {
+        let values = EventValues::default();
+        record_event(conn, sub as u32, ItemEventKind::Deleted, actor, values, now).await?;
+    }
+    Ok(trashed.into_iter().map(|id| id as u32).collect())
+}
+
+/// Fail with `Validation` unless `parent_id` names a live item that is
+/// neither `id` nor one of its subtasks
+async fn check_parent(
+    conn: &mut sqlx::SqliteConnection,
+    id: Option<u32>,
+    parent_id: u32,
+) -> AppResult<()> {
+    let live: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM items WHERE id = ? AND deleted_at IS NULL)",
+    )
+    .bind(parent_id as i64)
+    .fetch_one(&mut *conn)
+    .await?;
+    if !live {
+        return Err(parent_not_found(parent_id));
+    }
+    if let Some(id) = id {
+        let cycle: bool = sqlx::query_scalar(&format!(
+            "{SUBTREE_CTE} SELECT EXISTS (SELECT 1 FROM subtree WHERE id = ?)"
+        ))
+        .bind(id as i64)
+        .bind(parent_id as i64)
+        .fetch_one(&mut *conn)
+        .await?;
+        if cycle {
+            return Err(move_cycle(id));
+        }
+    }
+    Ok(())
+}
+
+/// An item's tag names, alphabetical
+async fn tags_of(conn: &mut sqlx::SqliteConnection, id: u32) -> AppResult<Vec<String>> {
+    Ok(sqlx::query_scalar(
+        "SELECT tags.name FROM item_tags JOIN tags ON tags.id = item_tags.tag_id \
+         WHERE item_tags.item_id = ? ORDER BY tags.name",
+    )
+    .bind(id as i64)
+    .fetch_all(&mut *conn)
+    .await?)
+}
+
+/// Fail with `NotFound` unless every id names a live item
+async fn ensure_live(tx: &mut sqlx::SqliteConnection, ids: &[u32]) -> AppResult<()> {
+    for &id in ids {
+        sqlx::query("SELECT 1 FROM items WHERE id = ? AND deleted_at IS NULL")
+            .bind(id as i64)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| item_not_found(id))?;
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl ItemService for SqliteItemService {
+    async fn list_all(&self) -> AppResult<Vec<Item>> {
+        let rows = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE deleted_at IS NULL ORDER BY position, id"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Item::from).collect())
+    }
+
+    async fn list_page(
+        &self,
+        query: &ItemQuery,
+        after: Option<u32>,
+        limit: u32,
+    ) -> AppResult<ItemPage> {
+        let (key, descending) = query.sort.sql_key();
+        let (cmp, order) = if descending {
+            ("<", "DESC")
+        } else {
+            (">", "ASC")
+        };
+
+        let mut sql = format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE items.deleted_at IS NULL{}",
+            query.status.sql_clause()
+        );
+        if query.top_level {
+            sql.push_str(" AND items.parent_id IS NULL");
+        }
+        if query.tag.is_some() {
+            sql.push_str(
+                " AND EXISTS (SELECT 1 FROM item_tags JOIN tags ON tags.id = item_tags.tag_id \
+                 WHERE item_tags.item_id = items.id AND tags.name = ?)",
+            );
+        }
+        if after.is_some() {
+            // Row-value comparison against the cursor item's own sort key
+            sql.push_str(&format!(
+                " AND ({key}, items.id) {cmp} (SELECT {key}, items.id FROM items WHERE items.id = ?)"
+            ));
+        }
+        sql.push_str(&format!(
+            " ORDER BY {key} {order}, items.id {order} LIMIT ?"
+        ));
+
+        let mut rows = sqlx::query_as::<_, ItemRow>(&sql);
+        if let Some(tag) = &query.tag {
+            rows = rows.bind(tag);
+        }
+        if let Some(after) = after {
+            rows = rows.bind(after as i64);
+        }
+        let rows = rows.bind(limit as i64 + 1).fetch_all(&self.pool).await?;
+        Ok(ItemPage::from_rows(
+            rows.into_iter().map(Item::from).collect(),
+            limit,
+        ))
+    }
+
+    async fn search(&self, query: &str, limit: u32) -> AppResult<Vec<Item>> {
+        let terms = search_terms(query);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Terms are alphanumeric-only, so quoting them can't inject FTS syntax
+        let fts_query = terms
+            .iter()
+            .map(|term| format!("\"{}\"*", term))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let rows = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items_fts JOIN items ON items.id = items_fts.rowid \
+             WHERE items_fts MATCH ? AND items.deleted_at IS NULL ORDER BY rank LIMIT ?"
+        ))
+        .bind(fts_query)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Item::from).collect())
+    }
+
+    async fn get_by_id(&self, id: u32) -> AppResult<Item> {
+        sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE id = ? AND deleted_at IS NULL"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&self.pool)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))
+    }
+
+    async fn create(&self, changes: ItemChanges, actor: &str) -> AppResult<Item> {
+        let tags = checked_tags(&changes.tags)?;
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        if let Some(parent_id) = changes.parent_id {
+            check_parent(&mut tx, None, parent_id).await?;
+        }
+
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, ItemRow>(&format!(
+            "INSERT INTO items \
+             (title, description, due_at, parent_id, created_at, updated_at, position) \
+             VALUES (?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(position), 0) + 1 FROM items)) \
+             RETURNING {ITEM_COLUMNS}"
+        ))
+        .bind(&changes.title)
+        .bind(&changes.description)
+        .bind(changes.due_at)
+        .bind(changes.parent_id.map(|p| p as i64))
+        .bind(now)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        let mut item = Item::from(row);
+        replace_tags(&mut tx, item.id, &tags).await?;
+        item.tags = tags;
+
+        let values = EventValues::created(&item);
+        record_event(&mut tx, item.id, ItemEventKind::Created, actor, values, now).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn import(&self, items: Vec<NewItem>, actor: &str) -> AppResult<usize> {
+        let tag_sets = items
+            .iter()
+            .map(|new| checked_tags(&new.tags))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        check_import_parents(&items)?;
+        let parents: Vec<Option<usize>> = items.iter().map(|new| new.parent).collect();
+
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let now = Utc::now();
+        let mut created = Vec::with_capacity(items.len());
+        for (new, tags) in items.into_iter().zip(tag_sets) {
+            let row = sqlx::query_as::<_, ItemRow>(&format!(
+                "INSERT INTO items \
+                 (title, description, done, due_at, created_at, updated_at, position) \
+                 VALUES (?, ?, ?, ?, ?, ?, (SELECT COALESCE(MAX(position), 0) + 1 FROM items)) \
+                 RETURNING {ITEM_COLUMNS}"
+            ))
+            .bind(&new.title)
+            .bind(&new.description)
+            .bind(new.done)
+            .bind(new.due_at)
+            .bind(now)
+            .bind(now)
+            .fetch_one(&mut *tx)
+            .await?;
+            let mut item = Item::from(row);
+            replace_tags(&mut tx, item.id, &tags).await?;
+            item.tags = tags;
+            created.push(item);
+        }
+
+        // Parents may come after their subtasks, so they are linked once
+        // every row has its id
+        let ids: Vec<u32> = created.iter().map(|item| item.id).collect();
+        for (item, parent) in created.iter_mut().zip(parents) {
+            if let Some(index) = parent {
+                item.parent_id = Some(ids[index]);
+                sqlx::query("UPDATE items SET parent_id = ? WHERE id = ?")
+                    .bind(ids[index] as i64)
+                    .bind(item.id as i64)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+            let values = EventValues::created(item);
+            record_event(&mut tx, item.id, ItemEventKind::Created, actor, values, now).await?;
+        }
+        tx.commit().await?;
+        Ok(created.len())
+    }
+
+    async fn update(
+        &self,
+        id: u32,
+        changes: ItemChanges,
+        expected_version: u32,
+        actor: &str,
+    ) -> AppResult<Item> {
+        let tags = checked_tags(&changes.tags)?;
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+
+        // The old values are read in the same transaction, for the history entry
+        let old = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE id = ? AND deleted_at IS NULL"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))?;
+        if let Some(parent_id) = changes.parent_id {
+            check_parent(&mut tx, Some(id), parent_id).await?;
+        }
+        if old.version != expected_version {
+            return Err(item_conflict(id));
+        }
+
+        let now = Utc::now();
+        let updated = sqlx::query(
+            "UPDATE items SET title = ?, description = ?, due_at = ?, parent_id = ?, \
+             updated_at = ?, version = version + 1 WHERE id = ? AND version = ?",
+        )
+        .bind(&changes.title)
+        .bind(&changes.description)
+        .bind(changes.due_at)
+        .bind(changes.parent_id.map(|p| p as i64))
+        .bind(now)
+        .bind(id as i64)
+        .bind(expected_version as i64)
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Err(item_conflict(id));
+        }
+        replace_tags(&mut tx, id, &tags).await?;
+        sqlx::query(DELETE_ORPHAN_TAGS).execute(&mut *tx).await?;
+
+        let row =
+            sqlx::query_as::<_, ItemRow>(&format!("SELECT {ITEM_COLUMNS} FROM items WHERE id = ?"))
                 .bind(id as i64)
-                .fetch_optional(&self.pool)
-                .await
-                .ok()
-                .flatten()
-                .map(Item::from)
+                .fetch_one(&mut *tx)
+                .await?;
+        let item = Item::from(row);
+
+        let values = EventValues::between(&old, &item);
+        if !values.is_empty() {
+            record_event(&mut tx, id, ItemEventKind::Updated, actor, values, now).await?;
+        }
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn toggle_done(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let now = Utc::now();
+        // Toggle done: flip 0↔1
+        let item = sqlx::query_as::<_, ItemRow>(&format!(
+            "UPDATE items SET done = CASE WHEN done = 0 THEN 1 ELSE 0 END, updated_at = ?, \
+             version = version + 1 \
+             WHERE id = ? AND deleted_at IS NULL RETURNING {ITEM_COLUMNS}"
+        ))
+        .bind(now)
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))?;
+
+        let values = EventValues::done(item.done);
+        record_event(&mut tx, id, ItemEventKind::Toggled, actor, values, now).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn reorder(&self, ids: &[u32]) -> AppResult<()> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+
+        let current: Vec<(i64, bool)> =
+            sqlx::query_as("SELECT id, deleted_at IS NULL FROM items ORDER BY position, id")
+                .fetch_all(&mut *tx)
+                .await?;
+        let current: Vec<(u32, bool)> = current
+            .into_iter()
+            .map(|(id, live)| (id as u32, live))
+            .collect();
+        let order = reordered(&current, ids)?;
+
+        // Only rows whose position actually changes are written
+        for (position, id) in (1_i64..).zip(order) {
+            sqlx::query("UPDATE items SET position = ? WHERE id = ? AND position != ?")
+                .bind(position)
+                .bind(id as i64)
+                .bind(position)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn set_tags(&self, id: u32, tags: Vec<String>, actor: &str) -> AppResult<Item> {
+        let tags = checked_tags(&tags)?;
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+
+        // Look up first, so a missing or trashed item aborts before tags are touched
+        let old = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE id = ? AND deleted_at IS NULL"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))?;
+        // Resubmitting the same (normalized) set is not an edit
+        if old.tags == tags {
+            return Ok(old);
+        }
+
+        let now = Utc::now();
+        sqlx::query("UPDATE items SET version = version + 1, updated_at = ? WHERE id = ?")
+            .bind(now)
+            .bind(id as i64)
+            .execute(&mut *tx)
+            .await?;
+        replace_tags(&mut tx, id, &tags).await?;
+        sqlx::query(DELETE_ORPHAN_TAGS).execute(&mut *tx).await?;
+
+        let row =
+            sqlx::query_as::<_, ItemRow>(&format!("SELECT {ITEM_COLUMNS} FROM items WHERE id = ?"))
+                .bind(id as i64)
+                .fetch_one(&mut *tx)
+                .await?;
+        let item = Item::from(row);
+
+        let values = EventValues::between(&old, &item);
+        if !values.is_empty() {
+            record_event(&mut tx, id, ItemEventKind::Updated, actor, values, now).await?;
+        }
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn set_due(
+        &self,
+        id: u32,
+        due_at: Option<DateTime<Utc>>,
+        actor: &str,
+    ) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let old = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE id = ? AND deleted_at IS NULL"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))?;
+
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, ItemRow>(&format!(
+            "UPDATE items SET due_at = ?, updated_at = ?, version = version + 1 \
+             WHERE id = ? RETURNING {ITEM_COLUMNS}"
+        ))
+        .bind(due_at)
+        .bind(now)
+        .bind(id as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let item = Item::from(row);
+
+        let values = EventValues::between(&old, &item);
+        record_event(&mut tx, id, ItemEventKind::Updated, actor, values, now).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn due_for_reminder(&self, until: DateTime<Utc>) -> AppResult<Vec<Item>> {
+        let rows = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items \
+             WHERE deleted_at IS NULL AND done = 0 AND due_at IS NOT NULL AND due_at <= ? \
+             AND (reminded_due_at IS NULL OR reminded_due_at != due_at) \
+             ORDER BY due_at, id"
+        ))
+        .bind(until)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Item::from).collect())
+    }
+
+    async fn mark_reminded(&self, id: u32, due_at: DateTime<Utc>) -> AppResult<()> {
+        sqlx::query("UPDATE items SET reminded_due_at = due_at WHERE id = ? AND due_at = ?")
+            .bind(id as i64)
+            .bind(due_at)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn list_tags(&self) -> AppResult<Vec<Tag>> {
+        let rows: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT tags.name, COUNT(*) FROM tags \
+             JOIN item_tags ON item_tags.tag_id = tags.id \
+             JOIN items ON items.id = item_tags.item_id AND items.deleted_at IS NULL \
+             GROUP BY tags.id ORDER BY tags.name",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .map(|(name, count)| Tag {
+                name,
+                item_count: count as u32,
             })
-        })
+            .collect())
+    }
+
+    async fn delete_tag(&self, name: &str, actor: &str) -> AppResult<()> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+
+        // Items losing the tag count as changed, so open edit forms go stale
+        let now = Utc::now();
+        let tagged: Vec<i64> = sqlx::query_scalar(
+            "UPDATE items SET version = version + 1, updated_at = ? WHERE id IN \
+             (SELECT item_id FROM item_tags JOIN tags ON tags.id = item_tags.tag_id \
+              WHERE tags.name = ?) RETURNING id",
+        )
+        .bind(now)
+        .bind(name)
+        .fetch_all(&mut *tx)
+        .await?;
+        for id in tagged {
+            let old = tags_of(&mut tx, id as u32).await?;
+            let new: Vec<String> = old.iter().filter(|t| *t != name).cloned().collect();
+            let values = EventValues::tags(&old, &new);
+            record_event(
+                &mut tx,
+                id as u32,
+                ItemEventKind::Updated,
+                actor,
+                values,
+                now,
+            )
+            .await?;
+        }
+
+        // item_tags rows go with it (ON DELETE CASCADE)
+        let deleted = sqlx::query("DELETE FROM tags WHERE name = ?")
+            .bind(name)
+            .execute(&mut *tx)
+            .await?;
+        if deleted.rows_affected() == 0 {
+            return Err(tag_not_found(name));
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn bulk_set_done(&self, ids: &[u32], done: bool, actor: &str) -> AppResult<usize> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let ids = unique_ids(ids);
+        ensure_live(&mut tx, &ids).await?;
+
+        let now = Utc::now();
+        let mut changed = 0;
+        for &id in &ids {
+            let updated = sqlx::query(
+                "UPDATE items SET done = ?, version = version + 1, updated_at = ? \
+                 WHERE id = ? AND done != ?",
+            )
+            .bind(done)
+            .bind(now)
+            .bind(id as i64)
+            .bind(done)
+            .execute(&mut *tx)
+            .await?;
+            if updated.rows_affected() > 0 {
+                let values = EventValues::done(done);
+                record_event(&mut tx, id, ItemEventKind::Toggled, actor, values, now).await?;
+                changed += 1;
+            }
+        }
+
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    async fn bulk_delete(&self, ids: &[u32], actor: &str) -> AppResult<usize> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let ids = unique_ids(ids);
+        ensure_live(&mut tx, &ids).await?;
+
+        let now = Utc::now();
+        for &id in &ids {
+            // Already gone if it was a subtask of an earlier id in the batch
+            trash_subtree(&mut tx, id, now, actor).await?;
+        }
+
+        tx.commit().await?;
+        Ok(ids.len())
     }
 
-    fn create(&self, title: String, description: String) -> Item {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let row = sqlx::query_as::<_, ItemRow>(
-                    "INSERT INTO items (title, description) VALUES (?, ?) RETURNING id, title, description, done"
+    async fn bulk_add_tag(&self, ids: &[u32], tag: &str, actor: &str) -> AppResult<usize> {
+        let tag = normalize_tag(tag).map_err(AppError::validation)?;
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let ids = unique_ids(ids);
+        ensure_live(&mut tx, &ids).await?;
+
+        sqlx::query("INSERT INTO tags (name) VALUES (?) ON CONFLICT (name) DO NOTHING")
+            .bind(&tag)
+            .execute(&mut *tx)
+            .await?;
+        let tag_id: i64 = sqlx::query_scalar("SELECT id FROM tags WHERE name = ?")
+            .bind(&tag)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        let now = Utc::now();
+        let mut changed = 0;
+        for &id in &ids {
+            let old = tags_of(&mut tx, id).await?;
+            if old.contains(&tag) {
+                continue;
+            }
+            if old.len() >= MAX_TAGS_PER_ITEM {
+                return Err(too_many_tags(id));
+            }
+
+            sqlx::query("INSERT INTO item_tags (item_id, tag_id) VALUES (?, ?)")
+                .bind(id as i64)
+                .bind(tag_id)
+                .execute(&mut *tx)
+                .await?;
+            sqlx::query("UPDATE items SET version = version + 1, updated_at = ? WHERE id = ?")
+                .bind(now)
+                .bind(id as i64)
+                .execute(&mut *tx)
+                .await?;
+            let mut new = old.clone();
+            new.push(tag.clone());
+            new.sort();
+            let values = EventValues::tags(&old, &new);
+            record_event(&mut tx, id, ItemEventKind::Updated, actor, values, now).await?;
+            changed += 1;
+        }
+
+        tx.commit().await?;
+        Ok(changed)
+    }
+
+    async fn delete(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        ensure_live(&mut tx, &[id]).await?;
+        trash_subtree(&mut tx, id, Utc::now(), actor).await?;
+
+        let row =
+            sqlx::query_as::<_, ItemRow>(&format!("SELECT {ITEM_COLUMNS} FROM items WHERE id = ?"))
+                .bind(id as i64)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+        Ok(Item::from(row))
+    }
+
+    async fn list_trash(&self) -> AppResult<Vec<Item>> {
+        let rows = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE deleted_at IS NOT NULL \
+             AND NOT EXISTS (SELECT 1 FROM items AS parent \
+                 WHERE parent.id = items.parent_id AND parent.deleted_at = items.deleted_at) \
+             ORDER BY deleted_at DESC, id DESC"
+        ))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(Item::from).collect())
+    }
+
+    async fn restore(&self, id: u32, actor: &str) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        // Subtasks trashed along with the item carry the very same timestamp;
+        // it is compared as stored text
+        let (deleted_at, parent_id): (String, Option<i64>) = sqlx::query_as(
+            "SELECT deleted_at, parent_id FROM items WHERE id = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| item_not_found(id))?;
+        let parent_live = match parent_id {
+            Some(parent_id) => {
+                sqlx::query("SELECT 1 FROM items WHERE id = ? AND deleted_at IS NULL")
+                    .bind(parent_id)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .is_some()
+            }
+            None => true,
+        };
+
+        let restored: Vec<i64> = sqlx::query_scalar(&format!(
+            "{SUBTREE_CTE} UPDATE items SET deleted_at = NULL, version = version + 1 \
+             WHERE id IN (SELECT id FROM subtree) AND deleted_at = ? RETURNING id"
+        ))
+        .bind(id as i64)
+        .bind(&deleted_at)
+        .fetch_all(&mut *tx)
+        .await?;
+        let mut root_values = EventValues::default();
+        if !parent_live {
+            sqlx::query("UPDATE items SET parent_id = NULL WHERE id = ?")
+                .bind(id as i64)
+                .execute(&mut *tx)
+                .await?;
+            root_values.set(
+                "parent",
+                parent_value(parent_id.map(|p| p as u32)),
+                None::<String>,
+            );
+        }
+
+        let now = Utc::now();
+        for sub in restored {
+            let values = if sub == id as i64 {
+                std::mem::take(&mut root_values)
+            } else {
+                EventValues::default()
+            };
+            record_event(
+                &mut tx,
+                sub as u32,
+                ItemEventKind::Restored,
+                actor,
+                values,
+                now,
+            )
+            .await?;
+        }
+
+        let row =
+            sqlx::query_as::<_, ItemRow>(&format!("SELECT {ITEM_COLUMNS} FROM items WHERE id = ?"))
+                .bind(id as i64)
+                .fetch_one(&mut *tx)
+                .await?;
+        tx.commit().await?;
+        Ok(Item::from(row))
+    }
+
+    async fn purge(&self, id: u32) -> AppResult<()> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        sqlx::query("SELECT 1 FROM items WHERE id = ? AND deleted_at IS NOT NULL")
+            .bind(id as i64)
+            .fetch_optional(&mut *tx)
+            .await?
+            .ok_or_else(|| item_not_found(id))?;
+
+        // Subtasks are deleted explicitly rather than by the foreign key, so
+        // the search-index triggers see every row go
+        sqlx::query(&format!(
+            "{SUBTREE_CTE} DELETE FROM items WHERE id IN (SELECT id FROM subtree)"
+        ))
+        .bind(id as i64)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn history(&self, id: u32) -> AppResult<Vec<ItemEvent>> {
+        sqlx::query("SELECT 1 FROM items WHERE id = ?")
+            .bind(id as i64)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| item_not_found(id))?;
+
+        let rows = sqlx::query_as::<_, EventRow>(
+            "SELECT id, item_id, kind, actor, old_values, new_values, created_at \
+             FROM item_events WHERE item_id = ? ORDER BY id DESC",
+        )
+        .bind(id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        rows.into_iter().map(ItemEvent::try_from).collect()
+    }
+
+    async fn list_children(&self, parent_id: u32) -> AppResult<Vec<Item>> {
+        let mut conn = self.pool.acquire().await?;
+        ensure_live(&mut conn, &[parent_id]).await?;
+        let rows = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE parent_id = ? AND deleted_at IS NULL \
+             ORDER BY position, id"
+        ))
+        .bind(parent_id as i64)
+        .fetch_all(&mut *conn)
+        .await?;
+        Ok(rows.into_iter().map(Item::from).collect())
+    }
+
+    async fn create_child(&self, parent_id: u32, title: String, actor: &str) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        ensure_live(&mut tx, &[parent_id]).await?;
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, ItemRow>(&format!(
+            "INSERT INTO items (title, description, parent_id, created_at, updated_at, position) \
+             VALUES (?, '', ?, ?, ?, (SELECT COALESCE(MAX(position), 0) + 1 FROM items)) \
+             RETURNING {ITEM_COLUMNS}"
+        ))
+        .bind(&title)
+        .bind(parent_id as i64)
+        .bind(now)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+        let item = Item::from(row);
+        let values = EventValues::created(&item);
+        record_event(&mut tx, item.id, ItemEventKind::Created, actor, values, now).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn move_to(&self, id: u32, parent_id: Option<u32>, actor: &str) -> AppResult<Item> {
+        let mut tx = crate::db::begin_write(&self.pool).await?;
+        let old = sqlx::query_as::<_, ItemRow>(&format!(
+            "SELECT {ITEM_COLUMNS} FROM items WHERE id = ? AND deleted_at IS NULL"
+        ))
+        .bind(id as i64)
+        .fetch_optional(&mut *tx)
+        .await?
+        .map(Item::from)
+        .ok_or_else(|| item_not_found(id))?;
+        if let Some(parent_id) = parent_id {
+            ensure_live(&mut tx, &[parent_id]).await?;
+            let cycle: bool = sqlx::query_scalar(&format!(
+                "{SUBTREE_CTE} SELECT EXISTS (SELECT 1 FROM subtree WHERE id = ?)"
+            ))
+            .bind(id as i64)
+            .bind(parent_id as i64)
+            .fetch_one(&mut *tx)
+            .await?;
+            if cycle {
+                return Err(move_cycle(id));
+            }
+        }
+        if old.parent_id == parent_id {
+            return Ok(old);
+        }
+
+        let now = Utc::now();
+        let row = sqlx::query_as::<_, ItemRow>(&format!(
+            "UPDATE items SET parent_id = ?, updated_at = ?, version = version + 1 \
+             WHERE id = ? RETURNING {ITEM_COLUMNS}"
+        ))
+        .bind(parent_id.map(|p| p as i64))
+        .bind(now)
+        .bind(id as i64)
+        .fetch_one(&mut *tx)
+        .await?;
+        let item = Item::from(row);
+
+        let values = EventValues::between(&old, &item);
+        record_event(&mut tx, id, ItemEventKind::Updated, actor, values, now).await?;
+        tx.commit().await?;
+        Ok(item)
+    }
+
+    async fn subtree(&self, id: u32) -> AppResult<Vec<u32>> {
+        let ids: Vec<i64> = sqlx::query_scalar(&format!(
+            "{SUBTREE_CTE} SELECT subtree.id FROM subtree JOIN items ON items.id = subtree.id"
+        ))
+        .bind(id as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        if ids.is_empty() {
+            return Err(item_not_found(id));
+        }
+        Ok(ids.into_iter().map(|id| id as u32).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    /// Both backends, freshly seeded alike — for tests that must hold on each
+    async fn backends() -> [Box<dyn ItemService>; 2] {
+        [
+            Box::new(SqliteItemService::new(
+                db::init_memory_pool().await.unwrap(),
+            )),
+            Box::new(InMemoryItemService::new()),
+        ]
+    }
+
+    fn changes(title: &str, description: &str) -> ItemChanges {
+        ItemChanges {
+            title: title.into(),
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    // Default #[tokio::test] is a current-thread runtime — the old
+    // block_in_place implementation panicked here.
+    #[tokio::test]
+    async fn test_sqlite_crud_on_current_thread_runtime() {
+        let service = SqliteItemService::new(db::init_memory_pool().await.unwrap());
+
+        let item = service
+            .create(changes("Write tests", ""), "tester")
+            .await
+            .unwrap();
+        assert!(!item.done);
+        assert_eq!(service.list_all().await.unwrap().len(), 4);
+
+        let toggled = service.toggle_done(item.id, "tester").await.unwrap();
+        assert!(toggled.done);
+
+        service.delete(item.id, "tester").await.unwrap();
+        assert!(matches!(
+            service.delete(item.id, "tester").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            service.get_by_id(item.id).await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trashed_items_can_be_restored_or_purged() {
+        let service = SqliteItemService::new(db::init_memory_pool().await.unwrap());
+
+        let trashed = service.delete(2, "tester").await.unwrap();
+        assert!(trashed.deleted_at.is_some());
+        assert_eq!(service.list_all().await.unwrap().len(), 2);
+        assert!(service.search("postgres", 10).await.unwrap().is_empty());
+        // Trashed items are read-only until restored
+        assert!(matches!(
+            service.toggle_done(2, "tester").await,
+            Err(AppError::NotFound(_))
+        ));
+        // Only trashed items may be purged
+        assert!(matches!(service.purge(3).await, Err(AppError::NotFound(_))));
+
+        let restored = service.restore(2, "tester").await.unwrap();
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(service.list_all().await.unwrap().len(), 3);
+
+        service.delete(2, "tester").await.unwrap();
+        assert_eq!(service.list_trash().await.unwrap().len(), 1);
+        service.purge(2).await.unwrap();
+        assert!(service.list_trash().await.unwrap().is_empty());
+        assert!(matches!(
+            service.restore(2, "tester").await,
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_toggle_missing_item() {
+        let service = InMemoryItemService::new();
+        assert!(matches!(
+            service.toggle_done(42, "tester").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert!(!service.toggle_done(1, "tester").await.unwrap().done);
+    }
+
+    #[tokio::test]
+    async fn test_keyset_pages_cover_every_item_once() {
+        let service = SqliteItemService::new(db::init_memory_pool().await.unwrap());
+        for n in 0..4 {
+            service
+                .create(changes(&format!("Item {}", n), ""), "tester")
+                .await
+                .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut after = None;
+        loop {
+            let page = service
+                .list_page(&ItemQuery::default(), after, 3)
+                .await
+                .unwrap();
+            assert!(page.items.len() <= 3);
+            seen.extend(page.items.iter().map(|i| i.id));
+            match page.next_cursor {
+                Some(cursor) => after = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(seen, (1..=7).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_filtered_sorted_pages_agree_across_backends() {
+        for service in backends().await {
+            for title in ["banana", "Apple", "cherry"] {
+                service.create(changes(title, ""), "tester").await.unwrap();
+            }
+            service.toggle_done(5, "tester").await.unwrap();
+
+            let cases = [
+                (StatusFilter::All, ItemSort::Newest, vec![6, 5, 4, 3, 2, 1]),
+                (StatusFilter::All, ItemSort::Updated, vec![5, 6, 4, 3, 2, 1]),
+                (StatusFilter::Pending, ItemSort::Title, vec![2, 4, 6, 3]),
+                (StatusFilter::Done, ItemSort::Oldest, vec![1, 5]),
+            ];
+            for (status, sort, expected) in cases {
+                let query = ItemQuery {
+                    status,
+                    sort,
+                    ..Default::default()
+                };
+                let mut seen = Vec::new();
+                let mut after = None;
+                loop {
+                    let page = service.list_page(&query, after, 2).await.unwrap();
+                    seen.extend(page.items.iter().map(|i| i.id));
+                    match page.next_cursor {
+                        Some(cursor) => after = Some(cursor),
+                        None => break,
+                    }
+                }
+                assert_eq!(seen, expected, "{:?}", query);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tags_filter_and_count_across_backends() {
+        for service in backends().await {
+            let tagged = service
+                .set_tags(
+                    2,
+                    vec!["Backend".into(), " urgent".into(), "backend".into()],
+                    "tester",
                 )
-                    .bind(&title)
-                    .bind(&description)
-                    .fetch_one(&self.pool)
+                .await
+                .unwrap();
+            assert_eq!(tagged.tags, ["backend", "urgent"]);
+            // The same set in another order and case is a no-op
+            let events = service.history(2).await.unwrap().len();
+            let again = service
+                .set_tags(2, vec!["URGENT".into(), "backend".into()], "tester")
+                .await
+                .unwrap();
+            assert_eq!(again.version, tagged.version);
+            assert_eq!(again.updated_at, tagged.updated_at);
+            assert_eq!(service.history(2).await.unwrap().len(), events);
+            service
+                .set_tags(3, vec!["urgent".into()], "tester")
+                .await
+                .unwrap();
+            assert!(matches!(
+                service
+                    .set_tags(3, vec!["no, commas".into()], "tester")
+                    .await,
+                Err(AppError::Validation(_))
+            ));
+
+            let query = ItemQuery {
+                tag: Some("urgent".into()),
+                ..Default::default()
+            };
+            let page = service.list_page(&query, None, 10).await.unwrap();
+            assert_eq!(page.items.iter().map(|i| i.id).collect::<Vec<_>>(), [2, 3]);
+
+            let counts: Vec<(String, u32)> = service
+                .list_tags()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.name, t.item_count))
+                .collect();
+            assert_eq!(counts, [("backend".into(), 1), ("urgent".into(), 2)]);
+
+            service.delete_tag("urgent", "cleaner").await.unwrap();
+            assert_eq!(service.get_by_id(2).await.unwrap().tags, ["backend"]);
+            assert!(service.get_by_id(3).await.unwrap().tags.is_empty());
+            let history = service.history(2).await.unwrap();
+            assert_eq!(history.len(), 2);
+            assert_eq!(history[0].actor, "cleaner");
+            assert_eq!(
+                history[0].changes,
+                [FieldChange {
+                    field: "tags".into(),
+                    before: "backend, urgent".into(),
+                    after: "backend".into(),
+                }]
+            );
+            assert_eq!(history[1].actor, "tester");
+            assert_eq!(history[1].changes[0].before, "");
+            assert_eq!(history[1].changes[0].after, "backend, urgent");
+            assert!(matches!(
+                service.delete_tag("urgent", "cleaner").await,
+                Err(AppError::NotFound(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorder_moves_only_listed_items() {
+        for service in backends().await {
+            let item = service
+                .create(changes("Fourth", ""), "tester")
+                .await
+                .unwrap();
+            assert_eq!(item.position, 4);
+            service.delete(2, "tester").await.unwrap();
+
+            // Swap 4 and 1 around the untouched 3
+            let before = service.get_by_id(1).await.unwrap();
+            service.reorder(&[4, 1]).await.unwrap();
+            let ids = |items: Vec<Item>| items.iter().map(|i| i.id).collect::<Vec<_>>();
+            assert_eq!(ids(service.list_all().await.unwrap()), [4, 3, 1]);
+
+            // Moving is not an edit: an open edit form of the item still saves
+            let after = service.get_by_id(1).await.unwrap();
+            assert_ne!(after.position, before.position);
+            assert_eq!(after.version, before.version);
+            assert_eq!(after.updated_at, before.updated_at);
+
+            // The trashed item kept its slot
+            service.restore(2, "tester").await.unwrap();
+            assert_eq!(ids(service.list_all().await.unwrap()), [4, 2, 3, 1]);
+
+            assert!(matches!(
+                service.reorder(&[1, 1]).await,
+                Err(AppError::Validation(_))
+            ));
+            service.delete(3, "tester").await.unwrap();
+            assert!(matches!(
+                service.reorder(&[3, 1]).await,
+                Err(AppError::NotFound(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_records_each_change_with_its_actor() {
+        for service in backends().await {
+            let item = service
+                .create(changes("Draft", ""), "session a")
+                .await
+                .unwrap();
+            let item = service
+                .update(item.id, changes("Final", ""), item.version, "session b")
+                .await
+                .unwrap();
+            service.toggle_done(item.id, "session a").await.unwrap();
+            service
+                .bulk_set_done(&[item.id], false, "session b")
+                .await
+                .unwrap();
+            // No-op and failed writes leave no trace
+            service
+                .bulk_set_done(&[item.id], false, "session c")
+                .await
+                .unwrap();
+            assert!(service
+                .update(item.id, changes("Stale", ""), item.version, "c")
+                .await
+                .is_err());
+            assert!(service.bulk_delete(&[item.id, 99], "c").await.is_err());
+            service.delete(item.id, "session a").await.unwrap();
+            service.restore(item.id, "session a").await.unwrap();
+
+            let history = service.history(item.id).await.unwrap();
+            let kinds: Vec<_> = history.iter().map(|e| e.kind).collect();
+            assert_eq!(
+                kinds,
+                [
+                    ItemEventKind::Restored,
+                    ItemEventKind::Deleted,
+                    ItemEventKind::Toggled,
+                    ItemEventKind::Toggled,
+                    ItemEventKind::Updated,
+                    ItemEventKind::Created,
+                ]
+            );
+            assert_eq!(history[2].actor, "session b");
+            assert_eq!(
+                history[2].changes,
+                [FieldChange {
+                    field: "done".into(),
+                    before: "true".into(),
+                    after: "false".into(),
+                }]
+            );
+            assert_eq!(
+                history[4].changes,
+                [FieldChange {
+                    field: "title".into(),
+                    before: "Draft".into(),
+                    after: "Final".into(),
+                }]
+            );
+
+            service.delete(item.id, "session a").await.unwrap();
+            service.purge(item.id).await.unwrap();
+            assert!(matches!(
+                service.history(item.id).await,
+                Err(AppError::NotFound(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operations_are_all_or_nothing() {
+        for service in backends().await {
+            // Item 1 is already done, so only two items change
+            assert_eq!(
+                service
+                    .bulk_set_done(&[1, 2, 3, 2], true, "tester")
                     .await
-                    .expect("Failed to insert item");
-                Item::from(row)
-            })
-        })
+                    .unwrap(),
+                2
+            );
+            assert_eq!(
+                service
+                    .bulk_add_tag(&[1, 2], "Review", "tester")
+                    .await
+                    .unwrap(),
+                2
+            );
+            assert_eq!(
+                service
+                    .bulk_add_tag(&[2, 3], "review", "tester")
+                    .await
+                    .unwrap(),
+                1
+            );
+            let history = service.history(3).await.unwrap();
+            assert_eq!(history[0].changes[0].field, "tags");
+            assert_eq!(history[0].changes[0].after, "review");
+
+            // One unknown id rolls back the whole batch
+            assert!(matches!(
+                service.bulk_set_done(&[1, 99], false, "tester").await,
+                Err(AppError::NotFound(_))
+            ));
+            assert!(service.get_by_id(1).await.unwrap().done);
+            assert!(matches!(
+                service.bulk_delete(&[3, 99], "tester").await,
+                Err(AppError::NotFound(_))
+            ));
+            assert_eq!(service.list_all().await.unwrap().len(), 3);
+
+            assert_eq!(service.bulk_delete(&[1, 3], "tester").await.unwrap(), 2);
+            assert_eq!(service.list_trash().await.unwrap().len(), 2);
+            assert_eq!(service.get_by_id(2).await.unwrap().tags, ["review"]);
+        }
     }
 
-    fn toggle_done(&self, id: u32) -> Option<Item> {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                // Toggle done: flip 0↔1
-                sqlx::query(
-                    "UPDATE items SET done = CASE WHEN done = 0 THEN 1 ELSE 0 END WHERE id = ?",
-                )
-                .bind(id as i64)
-                .execute(&self.pool)
+    #[tokio::test]
+    async fn test_import_is_all_or_nothing_across_backends() {
+        let row = |title: &str, tags: &[&str]| NewItem {
+            title: title.into(),
+            done: true,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        };
+
+        for service in backends().await {
+            let bad = vec![row("Fine", &[]), row("Broken", &["not ok"])];
+            assert!(matches!(
+                service.import(bad, "tester").await,
+                Err(AppError::Validation(_))
+            ));
+            assert_eq!(service.list_all().await.unwrap().len(), 3);
+
+            let good = vec![row("First", &["ops"]), row("Second", &[])];
+            assert_eq!(service.import(good, "tester").await.unwrap(), 2);
+            let items = service.list_all().await.unwrap();
+            assert_eq!(items.len(), 5);
+            assert_eq!(items[3].title, "First");
+            assert!(items[3].done);
+            assert_eq!(items[3].tags, ["ops"]);
+
+            // A subtask may come before its parent
+            let child = NewItem {
+                parent: Some(1),
+                ..row("Child", &[])
+            };
+            let tree = vec![child.clone(), row("Parent", &[])];
+            assert_eq!(service.import(tree, "tester").await.unwrap(), 2);
+            let items = service.list_all().await.unwrap();
+            assert_eq!(items[5].parent_id, Some(items[6].id));
+            // Parents count the subtasks they were imported with
+            assert_eq!((items[6].subtasks, items[6].subtasks_done), (1, 1));
+            assert!(matches!(
+                service.import(vec![child], "tester").await,
+                Err(AppError::Validation(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_applies_every_field_at_once_across_backends() {
+        let due_at = DateTime::parse_from_rfc3339("2030-01-01T09:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        for service in backends().await {
+            let item = service.get_by_id(3).await.unwrap();
+            let edit = ItemChanges {
+                title: "Ship it".into(),
+                description: item.description.clone(),
+                tags: vec!["release".into()],
+                due_at: Some(due_at),
+                parent_id: Some(2),
+            };
+
+            // The parent is checked before the version, and nothing is saved
+            for parent_id in [99, 3] {
+                let bad = ItemChanges {
+                    parent_id: Some(parent_id),
+                    ..edit.clone()
+                };
+                assert!(matches!(
+                    service.update(3, bad, item.version + 1, "tester").await,
+                    Err(AppError::Validation(_))
+                ));
+            }
+            assert_eq!(service.get_by_id(3).await.unwrap().title, "Deploy");
+
+            let updated = service
+                .update(3, edit.clone(), item.version, "tester")
                 .await
-                .ok()?;
+                .unwrap();
+            assert_eq!(updated.version, item.version + 1);
+            assert_eq!(updated.tags, ["release"]);
+            assert_eq!(updated.due_at, Some(due_at));
+            assert_eq!(updated.parent_id, Some(2));
+            assert_eq!(service.get_by_id(2).await.unwrap().subtasks, 1);
 
-                sqlx::query_as::<_, ItemRow>(
-                    "SELECT id, title, description, done FROM items WHERE id = ?",
+            let history = service.history(3).await.unwrap();
+            assert_eq!(history.len(), 1);
+            let fields: Vec<_> = history[0]
+                .changes
+                .iter()
+                .map(|c| c.field.as_str())
+                .collect();
+            assert_eq!(fields, ["due", "parent", "tags", "title"]);
+
+            assert!(matches!(
+                service.update(3, edit, item.version, "tester").await,
+                Err(AppError::Conflict(_))
+            ));
+
+            let created = service
+                .create(
+                    ItemChanges {
+                        title: "Smoke test".into(),
+                        tags: vec!["release".into()],
+                        parent_id: Some(3),
+                        ..Default::default()
+                    },
+                    "tester",
                 )
-                .bind(id as i64)
-                .fetch_optional(&self.pool)
                 .await
-                .ok()
-                .flatten()
-                .map(Item::from)
-            })
-        })
+                .unwrap();
+            assert_eq!(created.tags, ["release"]);
+            assert_eq!(created.parent_id, Some(3));
+            assert_eq!(service.history(created.id).await.unwrap().len(), 1);
+            assert!(matches!(
+                service
+                    .create(
+                        ItemChanges {
+                            parent_id: Some(99),
+                            ..changes("Orphan", "")
+                        },
+                        "tester"
+                    )
+                    .await,
+                Err(AppError::Validation(_))
+            ));
+        }
     }
 
-    fn delete(&self, id: u32) -> bool {
-        tokio::task::block_in_place(|| {
-            tokio::runtime::Handle::current().block_on(async {
-                let result = sqlx::query("DELETE FROM items WHERE id = ?")
-                    .bind(id as i64)
-                    .execute(&self.pool)
-                    .await;
-                matches!(result, Ok(r) if r.rows_affected() > 0)
-            })
-        })
+    #[tokio::test]
+    async fn test_stale_version_is_a_conflict() {
+        let service = SqliteItemService::new(db::init_memory_pool().await.unwrap());
+        let item = service.get_by_id(2).await.unwrap();
+
+        let updated = service
+            .update(
+                item.id,
+                changes("Add SQLite", "Done via SQLx"),
+                item.version,
+                "tester",
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.version, item.version + 1);
+
+        // A second editor still holding the original version loses
+        assert!(matches!(
+            service
+                .update(item.id, changes("Add Postgres", ""), item.version, "tester")
+                .await,
+            Err(AppError::Conflict(_))
+        ));
+        assert!(matches!(
+            service.update(99, changes("Nope", ""), 1, "tester").await,
+            Err(AppError::NotFound(_))
+        ));
+        assert_eq!(
+            service.get_by_id(item.id).await.unwrap().title,
+            "Add SQLite"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subtasks_roll_up_and_cascade_across_backends() {
+        for service in backends().await {
+            let a = service.create_child(2, "Step A".into(), "t").await.unwrap();
+            let b = service.create_child(2, "Step B".into(), "t").await.unwrap();
+            let c = service
+                .create_child(a.id, "Step C".into(), "t")
+                .await
+                .unwrap();
+            service.toggle_done(b.id, "t").await.unwrap();
+
+            let parent = service.get_by_id(2).await.unwrap();
+            assert_eq!((parent.subtasks, parent.subtasks_done), (2, 1));
+            let children = service.list_children(2).await.unwrap();
+            assert_eq!(
+                children.iter().map(|i| i.id).collect::<Vec<_>>(),
+                [a.id, b.id]
+            );
+            let query = ItemQuery {
+                top_level: true,
+                ..Default::default()
+            };
+            let page = service.list_page(&query, None, 10).await.unwrap();
+            assert!(page.items.iter().all(|i| i.parent_id.is_none()));
+
+            // An item can't move under itself or its own descendants
+            for target in [2, c.id] {
+                assert!(matches!(
+                    service.move_to(2, Some(target), "t").await,
+                    Err(AppError::Validation(_))
+                ));
+            }
+
+            // Trashing the parent takes the subtasks along; restoring brings them back
+            service.delete(2, "t").await.unwrap();
+            assert!(service.list_children(2).await.is_err());
+            let trash = service.list_trash().await.unwrap();
+            assert_eq!(trash.iter().map(|i| i.id).collect::<Vec<_>>(), [2]);
+            let parent = service.restore(2, "t").await.unwrap();
+            assert_eq!((parent.subtasks, parent.subtasks_done), (2, 1));
+            assert_eq!(service.get_by_id(c.id).await.unwrap().parent_id, Some(a.id));
+
+            let moved = service.move_to(c.id, None, "t").await.unwrap();
+            assert_eq!(moved.parent_id, None);
+            assert_eq!(service.get_by_id(a.id).await.unwrap().subtasks, 0);
+
+            // Purging removes the whole subtree, but not items moved out of it
+            service.delete(2, "t").await.unwrap();
+            let mut subtree = service.subtree(2).await.unwrap();
+            subtree.sort();
+            assert_eq!(subtree, [2, a.id, b.id]);
+            service.purge(2).await.unwrap();
+            assert!(service.list_trash().await.unwrap().is_empty());
+            assert!(service.get_by_id(c.id).await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fts_index_follows_writes() {
+        let service = SqliteItemService::new(db::init_memory_pool().await.unwrap());
+
+        // Seed rows are indexed by the migration
+        let hits = service.search("contain", 10).await.unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].title, "Deploy");
+
+        let item = service
+            .create(changes("Quarterly report", "Numbers for Q3"), "tester")
+            .await
+            .unwrap();
+        assert_eq!(service.search("quart numb", 10).await.unwrap().len(), 1);
+        // Operator soup must reach FTS5 as plain terms, not as query syntax
+        assert!(service.search("\"*( NEAR/2 -x", 10).await.is_ok());
+
+        service.delete(item.id, "tester").await.unwrap();
+        assert!(service.search("quarterly", 10).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_failure_is_an_error_not_empty_list() {
+        let pool = db::init_memory_pool().await.unwrap();
+        sqlx::query("DROP TABLE items")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let service = SqliteItemService::new(pool);
+
+        let err = service.list_all().await.unwrap_err();
+        assert!(matches!(err, AppError::Database(_)));
+        // SQLite's own message stays in the log
+        assert!(!err.to_string().contains("no such table"));
     }
 }
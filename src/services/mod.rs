@@ -5,16 +5,24 @@
 
 use std::sync::Arc;
 
+pub mod attachments;
+pub mod comments;
 pub mod csrf;
 pub mod health;
 pub mod items;
+pub mod reminders;
 pub mod session;
+pub mod storage;
 
+pub use attachments::AttachmentService;
+pub use comments::CommentService;
 pub use csrf::CsrfSecret;
 pub use health::HealthService;
 pub use items::ItemService;
-pub use session::{InMemorySessionStore, SessionStore};
+pub use session::{InMemorySessionStore, SessionStore, SqliteSessionStore};
+pub use storage::BlobStorage;
 
+use crate::config::{SessionConfig, SessionStoreKind};
 use crate::db::Db;
 
 /// Application services container — injected into handlers via State
@@ -22,19 +30,44 @@ use crate::db::Db;
 pub struct Services {
     pub health: Arc<dyn HealthService>,
     pub items: Arc<dyn ItemService>,
+    pub attachments: Arc<dyn AttachmentService>,
+    pub comments: Arc<dyn CommentService>,
     pub sessions: Arc<dyn SessionStore>,
     pub csrf: CsrfSecret,
 }
 
 impl Services {
-    /// Create services with SQLite-backed item storage
-    pub fn new_with_db(start_time: std::time::SystemTime, db: Db) -> Self {
-        Self {
+    /// Create services with SQLite-backed item storage; attachment files go
+    /// to `storage`. SQLite sessions also load (or create) the stored CSRF
+    /// secret, so their tokens stay valid across restarts.
+    pub async fn new_with_db(
+        start_time: std::time::SystemTime,
+        db: Db,
+        storage: Arc<dyn BlobStorage>,
+        session: &SessionConfig,
+    ) -> Result<Self, sqlx::Error> {
+        let (lifetime, max_sessions) = (session.lifetime(), session.max_sessions());
+        let (sessions, csrf): (Arc<dyn SessionStore>, _) = match session.store {
+            SessionStoreKind::Sqlite => (
+                Arc::new(SqliteSessionStore::new(db.clone(), lifetime, max_sessions)),
+                CsrfSecret::load_or_create(&db).await?,
+            ),
+            SessionStoreKind::Memory => (
+                Arc::new(InMemorySessionStore::new(lifetime, max_sessions)),
+                CsrfSecret::generate(),
+            ),
+        };
+        Ok(Self {
             health: Arc::new(health::DefaultHealthService::new(start_time)),
-            items: Arc::new(items::SqliteItemService::new(db)),
-            sessions: Arc::new(InMemorySessionStore::new()),
-            csrf: CsrfSecret::generate(),
-        }
+            items: Arc::new(items::SqliteItemService::new(db.clone())),
+            attachments: Arc::new(attachments::SqliteAttachmentService::new(
+                db.clone(),
+                storage,
+            )),
+            comments: Arc::new(comments::SqliteCommentService::new(db)),
+            sessions,
+            csrf,
+        })
     }
 
     /// Create services with in-memory implementations (fallback / tests)
@@ -42,7 +75,11 @@ impl Services {
         Self {
             health: Arc::new(health::DefaultHealthService::new(start_time)),
             items: Arc::new(items::InMemoryItemService::new()),
-            sessions: Arc::new(InMemorySessionStore::new()),
+            attachments: Arc::new(attachments::InMemoryAttachmentService::new(Arc::new(
+                storage::InMemoryStorage::new(),
+            ))),
+            comments: Arc::new(comments::InMemoryCommentService::new()),
+            sessions: Arc::new(InMemorySessionStore::default()),
             csrf: CsrfSecret::generate(),
         }
     }
@@ -0,0 +1,194 @@
+//! Reminder Service — notifications for items coming due
+//!
+//! A background job periodically looks for pending items due within a lead
+//! time and hands each one to a pluggable `Notifier`. Every due date is
+//! reminded about once; moving it re-arms the reminder. An item is only marked
+//! after its notifier succeeded, so failed deliveries are retried on the next
+//! scan (at-least-once).
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::AsyncWriteExt;
+use tokio::time::MissedTickBehavior;
+
+use super::items::ItemService;
+use crate::error::{AppError, AppResult};
+
+/// One reminder, as handed to a notifier
+#[derive(Debug, Clone, Serialize)]
+pub struct Reminder {
+    pub item_id: u32,
+    pub title: String,
+    pub due_at: DateTime<Utc>,
+    /// Already past due when the scan ran
+    pub overdue: bool,
+}
+
+/// Delivery channel for reminders — swap in email, chat, push, etc.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, reminder: &Reminder) -> AppResult<()>;
+}
+
+/// Writes reminders to the application log (the default). Only ids and
+/// dates are logged, never item content.
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn notify(&self, reminder: &Reminder) -> AppResult<()> {
+        tracing::info!(
+            item_id = reminder.item_id,
+            due_at = %reminder.due_at,
+            overdue = reminder.overdue,
+            "item due"
+        );
+        Ok(())
+    }
+}
+
+/// Appends each reminder as a JSON line to a file — for tests, or for
+/// another process to pick up
+pub struct FileOutboxNotifier {
+    path: PathBuf,
+}
+
+impl FileOutboxNotifier {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl Notifier for FileOutboxNotifier {
+    async fn notify(&self, reminder: &Reminder) -> AppResult<()> {
+        let mut line =
+            serde_json::to_string(reminder).map_err(|e| AppError::internal(e.to_string()))?;
+        line.push('\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| AppError::internal(format!("Reminder outbox: {}", e)))?;
+        // tokio files write in the background; flush so the line is on disk
+        // before the reminder counts as delivered
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| AppError::internal(format!("Reminder outbox: {}", e)))?;
+        file.flush()
+            .await
+            .map_err(|e| AppError::internal(format!("Reminder outbox: {}", e)))
+    }
+}
+
+/// Scans for items coming due and sends their reminders
+pub struct ReminderJob {
+    items: Arc<dyn ItemService>,
+    notifier: Arc<dyn Notifier>,
+    /// How long before its due date an item is reminded about
+    lead: Duration,
+}
+
+impl ReminderJob {
+    pub fn new(items: Arc<dyn ItemService>, notifier: Arc<dyn Notifier>, lead: Duration) -> Self {
+        Self {
+            items,
+            notifier,
+            lead,
+        }
+    }
+
+    /// One scan as of `now`; returns how many reminders went out
+    pub async fn run_once(&self, now: DateTime<Utc>) -> AppResult<usize> {
+        let mut sent = 0;
+        for item in self.items.due_for_reminder(now + self.lead).await? {
+            let Some(due_at) = item.due_at else {
+                continue;
+            };
+            let reminder = Reminder {
+                item_id: item.id,
+                title: item.title,
+                due_at,
+                overdue: due_at < now,
+            };
+            match self.notifier.notify(&reminder).await {
+                Ok(()) => {
+                    self.items.mark_reminded(item.id, due_at).await?;
+                    sent += 1;
+                }
+                Err(e) => tracing::warn!(
+                    item_id = item.id,
+                    error = %e,
+                    "reminder not delivered, retrying next scan"
+                ),
+            }
+        }
+        Ok(sent)
+    }
+
+    /// Run a scan every `interval` on a background task
+    pub fn spawn(self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.run_once(Utc::now()).await {
+                    tracing::error!(error = %e, "reminder scan failed");
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::items::InMemoryItemService;
+
+    #[tokio::test]
+    async fn test_each_due_date_is_reminded_once() {
+        let items: Arc<dyn ItemService> = Arc::new(InMemoryItemService::new());
+        let outbox = std::env::temp_dir().join(format!("outbox-{}.jsonl", uuid::Uuid::new_v4()));
+        let job = ReminderJob::new(
+            items.clone(),
+            Arc::new(FileOutboxNotifier::new(&outbox)),
+            Duration::hours(1),
+        );
+        let now = Utc::now();
+
+        items
+            .set_due(2, Some(now + Duration::minutes(30)), "t")
+            .await
+            .unwrap();
+        items
+            .set_due(3, Some(now + Duration::days(2)), "t")
+            .await
+            .unwrap();
+        // Done items need no reminder
+        items
+            .set_due(1, Some(now - Duration::hours(1)), "t")
+            .await
+            .unwrap();
+
+        assert_eq!(job.run_once(now).await.unwrap(), 1);
+        assert_eq!(job.run_once(now).await.unwrap(), 0);
+
+        // Moving the due date re-arms the reminder
+        items
+            .set_due(2, Some(now + Duration::minutes(45)), "t")
+            .await
+            .unwrap();
+        assert_eq!(job.run_once(now).await.unwrap(), 1);
+
+        let sent = std::fs::read_to_string(&outbox).unwrap();
+        std::fs::remove_file(&outbox).unwrap();
+        assert_eq!(sent.lines().count(), 2);
+        assert!(sent.lines().all(|line| line.contains("\"item_id\":2")));
+    }
+}
@@ -3,113 +3,988 @@
 //! Provides server-side session management with:
 //! - Random 256-bit session IDs
 //! - HttpOnly, Secure, SameSite=Strict cookies
-//! - In-memory session store (swap for Redis/DB in production)
-//! - Automatic cleanup of expired sessions
+//! - In-memory or SQLite session store (`[session] store` in the config);
+//!   SQLite sessions survive restarts and deploys
+//! - Idle timeout, absolute lifetime and opt-in remember-me, all configured
+//!   under `[session]` (see `SessionLifetime`)
+//! - Sessions are only stored once a request needs one (see
+//!   `CurrentSession::was_used`), capped at `[session] max_sessions` by
+//!   evicting the least recently used
+//! - Expired sessions are deleted by a background reaper (`spawn_reaper`)
 
+use async_trait::async_trait;
+use axum::{
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponseParts, ResponseParts},
+};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
 use rand::RngCore;
-use std::collections::HashMap;
-use std::sync::RwLock;
-use std::time::{Duration, Instant};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::collections::{BTreeSet, HashMap};
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use tokio::time::MissedTickBehavior;
+
+use crate::config::SessionConfig;
+use crate::error::{AppError, AppResult};
 
 /// Session cookie name — intentionally generic to avoid fingerprinting
 pub const SESSION_COOKIE: &str = "__Host-sid";
 
-/// Session lifetime
-const SESSION_TTL: Duration = Duration::from_secs(3600); // 1 hour
+/// How long sessions last (`[session]` in the config). A session expires
+/// `idle` after its last request, and `absolute` after it was created however
+/// busy it is. With remember-me configured, sessions that opt in (see
+/// `CurrentSession::remember`) are kept for `remember` instead — both
+/// without requests and, at least, in total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionLifetime {
+    pub idle: Duration,
+    pub absolute: Duration,
+    pub remember: Option<Duration>,
+}
+
+impl SessionLifetime {
+    /// When `session` expires unless another request comes first
+    pub fn expires_at(&self, session: &Session) -> DateTime<Utc> {
+        let (idle, absolute) = match self.remember {
+            Some(remember) if session.remember => (remember, remember.max(self.absolute)),
+            _ => (self.idle, self.absolute),
+        };
+        (session.last_access + idle).min(session.created_at + absolute)
+    }
+}
+
+impl Default for SessionLifetime {
+    fn default() -> Self {
+        SessionConfig::default().lifetime()
+    }
+}
+
+/// Label naming a session in audit records. The id itself is a bearer
+/// credential, so only a short hash of it is ever stored or shown.
+pub fn session_actor(session_id: &str) -> String {
+    let digest = Sha256::digest(session_id.as_bytes());
+    format!("session {}", &hex::encode(digest)[..8])
+}
+
+/// Session data key holding pending flash messages
+const FLASH_KEY: &str = "_flash";
+
+/// How a flash message is shown — the `alert-*` class it renders with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashLevel {
+    Success,
+    Info,
+    Warning,
+    Danger,
+}
 
-/// Session data stored server-side
+impl FlashLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlashLevel::Success => "success",
+            FlashLevel::Info => "info",
+            FlashLevel::Warning => "warning",
+            FlashLevel::Danger => "danger",
+        }
+    }
+}
+
+// Templates print the level as its class suffix (askama uses Display,
+// minijinja the serde form — both give e.g. `success`)
+impl fmt::Display for FlashLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A one-time message for the next response the user sees, e.g. "Item
+/// saved" after a redirect
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Flash {
+    pub level: FlashLevel,
+    pub message: String,
+}
+
+/// Session data stored server-side. Times are wall-clock, so they mean the
+/// same thing after a restart.
 #[derive(Debug, Clone)]
 pub struct Session {
     pub id: String,
     pub csrf_token: String,
-    pub created_at: Instant,
-    pub last_access: Instant,
+    pub created_at: DateTime<Utc>,
+    pub last_access: DateTime<Utc>,
+    /// Opted into remember-me
+    pub remember: bool,
     pub data: HashMap<String, String>,
 }
 
 impl Session {
-    pub fn is_expired(&self) -> bool {
-        self.last_access.elapsed() > SESSION_TTL
+    /// A fresh session with a random id, not yet in any store
+    pub fn new() -> Self {
+        let now = Utc::now();
+        Self {
+            id: generate_id(),
+            csrf_token: String::new(),
+            created_at: now,
+            last_access: now,
+            remember: false,
+            data: HashMap::new(),
+        }
+    }
+
+    pub fn is_expired(&self, lifetime: &SessionLifetime) -> bool {
+        Utc::now() >= lifetime.expires_at(self)
+    }
+
+    /// The value stored under `key`, if there is one of type `T`
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let json = self.data.get(key)?;
+        serde_json::from_str(json).ok()
+    }
+
+    /// Store `value` under `key` (as JSON), replacing any previous value
+    pub fn set<T: Serialize>(&mut self, key: &str, value: &T) -> AppResult<()> {
+        let json = serde_json::to_string(value)
+            .map_err(|e| AppError::internal(format!("Session value {:?}: {}", key, e)))?;
+        self.data.insert(key.to_string(), json);
+        Ok(())
+    }
+
+    /// Remove the value under `key`, returning it if it was of type `T`
+    pub fn remove<T: DeserializeOwned>(&mut self, key: &str) -> Option<T> {
+        let json = self.data.remove(key)?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The session of the current request — resolved by `session_middleware`,
+/// which puts it in the request extensions, and taken by handlers as an
+/// extractor. Clones share one session: data changed through `set`/`remove`
+/// is written back to the store after the handler has run.
+#[derive(Clone)]
+pub struct CurrentSession(Arc<Mutex<SessionState>>);
+
+struct SessionState {
+    session: Session,
+    is_new: bool,
+    changed: bool,
+    used: bool,
+    remember: Option<bool>,
+}
+
+impl CurrentSession {
+    /// Wrap a session; `is_new` if it was created for this request
+    pub fn new(session: Session, is_new: bool) -> Self {
+        Self(Arc::new(Mutex::new(SessionState {
+            session,
+            is_new,
+            changed: false,
+            used: false,
+            remember: None,
+        })))
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, SessionState> {
+        self.0.lock().unwrap()
+    }
+
+    pub fn id(&self) -> String {
+        self.state().session.id.clone()
+    }
+
+    /// CSRF token issued for this request (also sent as `X-CSRF-Token`)
+    pub fn csrf_token(&self) -> String {
+        let mut state = self.state();
+        state.used = true;
+        state.session.csrf_token.clone()
+    }
+
+    /// Created by this request: no valid session cookie came with it
+    pub fn is_new(&self) -> bool {
+        self.state().is_new
+    }
+
+    /// This session's label in audit records (see `session_actor`)
+    pub fn actor(&self) -> String {
+        session_actor(&self.state().session.id)
+    }
+
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        self.state().session.get(key)
+    }
+
+    pub fn set<T: Serialize>(&self, key: &str, value: &T) -> AppResult<()> {
+        let mut state = self.state();
+        state.session.set(key, value)?;
+        state.changed = true;
+        state.used = true;
+        Ok(())
+    }
+
+    pub fn remove<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let mut state = self.state();
+        let had_key = state.session.data.contains_key(key);
+        state.changed |= had_key;
+        state.used |= had_key;
+        state.session.remove(key)
+    }
+
+    /// Queue a flash message. Full pages render pending messages through
+    /// `base.html`; after an HTMX request `session_middleware` appends them
+    /// as an out-of-band toast.
+    pub fn push_flash(&self, level: FlashLevel, message: impl Into<String>) -> AppResult<()> {
+        let mut flashes: Vec<Flash> = self.get(FLASH_KEY).unwrap_or_default();
+        flashes.push(Flash {
+            level,
+            message: message.into(),
+        });
+        self.set(FLASH_KEY, &flashes)
+    }
+
+    pub fn has_flashes(&self) -> bool {
+        self.state().session.data.contains_key(FLASH_KEY)
+    }
+
+    /// Take all pending flash messages, oldest first
+    pub fn drain_flashes(&self) -> Vec<Flash> {
+        self.remove(FLASH_KEY).unwrap_or_default()
+    }
+
+    /// The stored JSON under `key`, untyped (see `SessionStore::destroy_where`)
+    pub fn data_value(&self, key: &str) -> Option<String> {
+        self.state().session.data.get(key).cloned()
+    }
+
+    /// Turn remember-me on (e.g. from a login form's checkbox) or off. The
+    /// session then lasts `[session] remember_me_days`, as does its cookie;
+    /// without that setting this has no effect.
+    pub fn remember(&self, on: bool) {
+        let mut state = self.state();
+        state.remember = Some(on);
+        state.used = true;
+    }
+
+    /// The handler handed out this session's CSRF token or changed the
+    /// session. A new session is only stored (and its cookie set) then, so
+    /// requests that never need one — static files, health checks, bots
+    /// probing URLs — don't fill the store.
+    pub fn was_used(&self) -> bool {
+        self.state().used
+    }
+
+    /// A remember-me change asked for by the handler, once
+    pub fn take_remember(&self) -> Option<bool> {
+        self.state().remember.take()
+    }
+
+    /// The session data to write back, if it changed since the last call
+    pub fn take_changes(&self) -> Option<HashMap<String, String>> {
+        let mut state = self.state();
+        if !std::mem::take(&mut state.changed) {
+            return None;
+        }
+        Some(state.session.data.clone())
+    }
+}
+
+#[async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for CurrentSession {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<CurrentSession>()
+            .cloned()
+            .ok_or_else(|| AppError::internal("No session: session_middleware is not installed"))
+    }
+}
+
+/// A fresh random session id
+fn generate_id() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// A change to the request's session that a handler asks for by returning it
+/// as part of its response, e.g. `(SessionAction::Rotate, html)`.
+/// `session_middleware` carries it out after the handler, so the cookie and
+/// CSRF token in the response already match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionAction {
+    /// Move the session's data to a fresh id and reissue the cookie — on any
+    /// privilege change such as login, against session fixation. The CSRF
+    /// token changes with it (sent as `X-CSRF-Token` as usual).
+    Rotate,
+    /// End the session and clear the cookie (logout)
+    Destroy,
+    /// "Log out everywhere": end every session whose data holds the same
+    /// value under this key as the current one (e.g. a user id), then this one
+    DestroyAllSharing(String),
+}
+
+impl IntoResponseParts for SessionAction {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions_mut().insert(self);
+        Ok(res)
     }
 }
 
 /// Session store trait — allows swapping in-memory for Redis, DB, etc.
+#[async_trait]
 pub trait SessionStore: Send + Sync {
-    fn create(&self) -> Session;
-    fn get(&self, id: &str) -> Option<Session>;
-    fn touch(&self, id: &str);
-    fn update_csrf(&self, id: &str, token: &str);
-    fn destroy(&self, id: &str);
-    fn cleanup_expired(&self);
+    /// The expiry policy this store enforces
+    fn lifetime(&self) -> SessionLifetime;
+    /// Store a new session. Past the store's session cap, the least recently
+    /// used sessions are evicted to make room.
+    async fn insert(&self, session: &Session);
+    async fn create(&self) -> Session {
+        let session = Session::new();
+        self.insert(&session).await;
+        session
+    }
+    /// The live session with this id; expired ones count as missing
+    async fn get(&self, id: &str) -> Option<Session>;
+    /// Record a request on `session`, extending its expiry
+    async fn touch(&self, session: &mut Session);
+    async fn update_csrf(&self, id: &str, token: &str);
+    /// Turn remember-me on or off; returns the updated session
+    async fn set_remember(&self, id: &str, remember: bool) -> Option<Session>;
+    /// Replace the session's data (see `CurrentSession`)
+    async fn save_data(&self, id: &str, data: &HashMap<String, String>);
+    /// Move a live session to a fresh id; the old id stops working. `None`
+    /// if there was no such session.
+    async fn rotate(&self, id: &str) -> Option<Session>;
+    async fn destroy(&self, id: &str);
+    /// Destroy every session whose data holds `value` (JSON, as stored by
+    /// `Session::set`) under `key`; returns how many there were
+    async fn destroy_where(&self, key: &str, value: &str) -> usize;
+    /// Delete expired sessions; returns how many there were
+    async fn cleanup_expired(&self) -> usize;
+}
+
+/// Delete expired sessions from `store` every `interval` on a background
+/// task (lookups already ignore them; this frees the space)
+pub fn spawn_reaper(
+    store: Arc<dyn SessionStore>,
+    interval: std::time::Duration,
+) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let reaped = store.cleanup_expired().await;
+            if reaped > 0 {
+                tracing::debug!(reaped, "expired sessions deleted");
+            }
+        }
+    })
+}
+
+/// Sessions by id, indexed by last access as well so the least recently
+/// used one is found in O(log n). Only `insert`, `remove` and `touch` may
+/// change a session's id or `last_access`, to keep the two in step.
+#[derive(Default)]
+struct SessionMap {
+    by_id: HashMap<String, Session>,
+    by_access: BTreeSet<(DateTime<Utc>, String)>,
+}
+
+impl SessionMap {
+    fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    fn get(&self, id: &str) -> Option<&Session> {
+        self.by_id.get(id)
+    }
+
+    /// For changes to anything but the id and `last_access`
+    fn get_mut(&mut self, id: &str) -> Option<&mut Session> {
+        self.by_id.get_mut(id)
+    }
+
+    fn insert(&mut self, session: Session) {
+        self.remove(&session.id);
+        self.by_access
+            .insert((session.last_access, session.id.clone()));
+        self.by_id.insert(session.id.clone(), session);
+    }
+
+    fn remove(&mut self, id: &str) -> Option<Session> {
+        let session = self.by_id.remove(id)?;
+        self.by_access
+            .remove(&(session.last_access, session.id.clone()));
+        Some(session)
+    }
+
+    fn touch(&mut self, id: &str, at: DateTime<Utc>) {
+        if let Some(session) = self.by_id.get_mut(id) {
+            self.by_access
+                .remove(&(session.last_access, session.id.clone()));
+            session.last_access = at;
+            self.by_access.insert((at, session.id.clone()));
+        }
+    }
+
+    fn remove_least_recent(&mut self) -> Option<Session> {
+        let (_, id) = self.by_access.pop_first()?;
+        self.by_id.remove(&id)
+    }
+
+    /// Keep the sessions `keep` accepts; returns how many were removed
+    fn retain(&mut self, keep: impl Fn(&Session) -> bool) -> usize {
+        let doomed: Vec<String> = self
+            .by_id
+            .values()
+            .filter(|s| !keep(s))
+            .map(|s| s.id.clone())
+            .collect();
+        for id in &doomed {
+            self.remove(id);
+        }
+        doomed.len()
+    }
 }
 
 /// In-memory session store (suitable for single-instance deployments)
 pub struct InMemorySessionStore {
-    sessions: RwLock<HashMap<String, Session>>,
+    sessions: RwLock<SessionMap>,
+    lifetime: SessionLifetime,
+    max_sessions: Option<usize>,
 }
 
 impl InMemorySessionStore {
-    pub fn new() -> Self {
+    pub fn new(lifetime: SessionLifetime, max_sessions: Option<usize>) -> Self {
         Self {
-            sessions: RwLock::new(HashMap::new()),
+            sessions: RwLock::new(SessionMap::default()),
+            lifetime,
+            max_sessions,
         }
     }
-
-    fn generate_id() -> String {
-        let mut bytes = [0u8; 32];
-        rand::thread_rng().fill_bytes(&mut bytes);
-        URL_SAFE_NO_PAD.encode(bytes)
-    }
 }
 
 impl Default for InMemorySessionStore {
     fn default() -> Self {
-        Self::new()
+        Self::new(SessionLifetime::default(), None)
     }
 }
 
+#[async_trait]
 impl SessionStore for InMemorySessionStore {
-    fn create(&self) -> Session {
-        let session = Session {
-            id: Self::generate_id(),
-            csrf_token: String::new(),
-            created_at: Instant::now(),
-            last_access: Instant::now(),
-            data: HashMap::new(),
+    fn lifetime(&self) -> SessionLifetime {
+        self.lifetime
+    }
+
+    async fn insert(&self, session: &Session) {
+        let mut sessions = self.sessions.write().unwrap();
+        sessions.insert(session.clone());
+        let Some(max) = self.max_sessions else {
+            return;
         };
-        self.sessions
-            .write()
-            .unwrap()
-            .insert(session.id.clone(), session.clone());
-        session
+        while sessions.len() > max {
+            if sessions.remove_least_recent().is_none() {
+                break;
+            }
+        }
     }
 
-    fn get(&self, id: &str) -> Option<Session> {
+    async fn get(&self, id: &str) -> Option<Session> {
         let sessions = self.sessions.read().unwrap();
-        sessions.get(id).filter(|s| !s.is_expired()).cloned()
+        sessions
+            .get(id)
+            .filter(|s| !s.is_expired(&self.lifetime))
+            .cloned()
+    }
+
+    async fn touch(&self, session: &mut Session) {
+        session.last_access = Utc::now();
+        self.sessions
+            .write()
+            .unwrap()
+            .touch(&session.id, session.last_access);
     }
 
-    fn touch(&self, id: &str) {
+    async fn update_csrf(&self, id: &str, token: &str) {
         if let Some(session) = self.sessions.write().unwrap().get_mut(id) {
-            session.last_access = Instant::now();
+            session.csrf_token = token.to_string();
         }
     }
 
-    fn update_csrf(&self, id: &str, token: &str) {
+    async fn set_remember(&self, id: &str, remember: bool) -> Option<Session> {
+        let mut sessions = self.sessions.write().unwrap();
+        let session = sessions
+            .get_mut(id)
+            .filter(|s| !s.is_expired(&self.lifetime))?;
+        session.remember = remember;
+        Some(session.clone())
+    }
+
+    async fn save_data(&self, id: &str, data: &HashMap<String, String>) {
         if let Some(session) = self.sessions.write().unwrap().get_mut(id) {
-            session.csrf_token = token.to_string();
+            session.data = data.clone();
         }
     }
 
-    fn destroy(&self, id: &str) {
+    async fn rotate(&self, id: &str) -> Option<Session> {
+        let mut sessions = self.sessions.write().unwrap();
+        let mut session = sessions
+            .remove(id)
+            .filter(|s| !s.is_expired(&self.lifetime))?;
+        session.id = generate_id();
+        session.last_access = Utc::now();
+        sessions.insert(session.clone());
+        Some(session)
+    }
+
+    async fn destroy(&self, id: &str) {
         self.sessions.write().unwrap().remove(id);
     }
 
-    fn cleanup_expired(&self) {
+    async fn destroy_where(&self, key: &str, value: &str) -> usize {
         self.sessions
             .write()
             .unwrap()
-            .retain(|_, s| !s.is_expired());
+            .retain(|s| s.data.get(key).map(String::as_str) != Some(value))
+    }
+
+    async fn cleanup_expired(&self) -> usize {
+        self.sessions
+            .write()
+            .unwrap()
+            .retain(|s| !s.is_expired(&self.lifetime))
+    }
+}
+
+/// SQLite-backed session store (the `sessions` table). Rows are keyed by a
+/// SHA-256 hash of the session id, so the database never holds a usable
+/// cookie value. The trait has no error channel: database failures are
+/// logged, and an unreadable session counts as missing.
+pub struct SqliteSessionStore {
+    pool: SqlitePool,
+    lifetime: SessionLifetime,
+    max_sessions: Option<usize>,
+}
+
+impl SqliteSessionStore {
+    pub fn new(pool: SqlitePool, lifetime: SessionLifetime, max_sessions: Option<usize>) -> Self {
+        Self {
+            pool,
+            lifetime,
+            max_sessions,
+        }
+    }
+}
+
+/// Primary key of a session row
+fn id_hash(id: &str) -> String {
+    hex::encode(Sha256::digest(id.as_bytes()))
+}
+
+fn log_store_error(op: &str, err: impl std::fmt::Display) {
+    tracing::error!(op, error = %err, "session store error");
+}
+
+/// Row type for `sessions` (without the key and expiry)
+#[derive(sqlx::FromRow)]
+struct SessionRow {
+    csrf_token: String,
+    data: String,
+    created_at: DateTime<Utc>,
+    last_access: DateTime<Utc>,
+    remember: bool,
+}
+
+#[async_trait]
+impl SessionStore for SqliteSessionStore {
+    fn lifetime(&self) -> SessionLifetime {
+        self.lifetime
+    }
+
+    async fn insert(&self, session: &Session) {
+        let data = match serde_json::to_string(&session.data) {
+            Ok(data) => data,
+            Err(e) => return log_store_error("insert", e),
+        };
+        let result = sqlx::query(
+            "INSERT INTO sessions \
+             (id_hash, csrf_token, data, created_at, last_access, remember, expires_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(id_hash(&session.id))
+        .bind(&session.csrf_token)
+        .bind(data)
+        .bind(session.created_at)
+        .bind(session.last_access)
+        .bind(session.remember)
+        .bind(self.lifetime.expires_at(session))
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = result {
+            return log_store_error("insert", e);
+        }
+
+        let Some(max) = self.max_sessions else {
+            return;
+        };
+        let result = sqlx::query(
+            "DELETE FROM sessions WHERE id_hash IN \
+             (SELECT id_hash FROM sessions ORDER BY last_access \
+              LIMIT max(0, (SELECT COUNT(*) FROM sessions) - ?))",
+        )
+        .bind(max as i64)
+        .execute(&self.pool)
+        .await;
+        if let Err(e) = result {
+            log_store_error("insert", e);
+        }
+    }
+
+    async fn get(&self, id: &str) -> Option<Session> {
+        let row = sqlx::query_as::<_, SessionRow>(
+            "SELECT csrf_token, data, created_at, last_access, remember FROM sessions \
+             WHERE id_hash = ? AND expires_at > ?",
+        )
+        .bind(id_hash(id))
+        .bind(Utc::now())
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| log_store_error("get", e))
+        .ok()??;
+        let data = serde_json::from_str(&row.data)
+            .map_err(|e| log_store_error("get", e))
+            .ok()?;
+        let session = Session {
+            id: id.to_string(),
+            csrf_token: row.csrf_token,
+            created_at: row.created_at,
+            last_access: row.last_access,
+            remember: row.remember,
+            data,
+        };
+        // `expires_at` was computed under the settings of its last write
+        (!session.is_expired(&self.lifetime)).then_some(session)
+    }
+
+    async fn touch(&self, session: &mut Session) {
+        session.last_access = Utc::now();
+        let result =
+            sqlx::query("UPDATE sessions SET last_access = ?, expires_at = ? WHERE id_hash = ?")
+                .bind(session.last_access)
+                .bind(self.lifetime.expires_at(session))
+                .bind(id_hash(&session.id))
+                .execute(&self.pool)
+                .await;
+        if let Err(e) = result {
+            log_store_error("touch", e);
+        }
+    }
+
+    async fn update_csrf(&self, id: &str, token: &str) {
+        let result = sqlx::query("UPDATE sessions SET csrf_token = ? WHERE id_hash = ?")
+            .bind(token)
+            .bind(id_hash(id))
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            log_store_error("update_csrf", e);
+        }
+    }
+
+    async fn set_remember(&self, id: &str, remember: bool) -> Option<Session> {
+        let mut session = self.get(id).await?;
+        session.remember = remember;
+        let result =
+            sqlx::query("UPDATE sessions SET remember = ?, expires_at = ? WHERE id_hash = ?")
+                .bind(remember)
+                .bind(self.lifetime.expires_at(&session))
+                .bind(id_hash(id))
+                .execute(&self.pool)
+                .await;
+        match result {
+            Ok(_) => Some(session),
+            Err(e) => {
+                log_store_error("set_remember", e);
+                None
+            }
+        }
+    }
+
+    async fn save_data(&self, id: &str, data: &HashMap<String, String>) {
+        let json = match serde_json::to_string(data) {
+            Ok(json) => json,
+            Err(e) => return log_store_error("save_data", e),
+        };
+        let result = sqlx::query("UPDATE sessions SET data = ? WHERE id_hash = ?")
+            .bind(json)
+            .bind(id_hash(id))
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            log_store_error("save_data", e);
+        }
+    }
+
+    async fn rotate(&self, id: &str) -> Option<Session> {
+        let mut session = self.get(id).await?;
+        session.id = generate_id();
+        session.last_access = Utc::now();
+        let result = sqlx::query(
+            "UPDATE sessions SET id_hash = ?, last_access = ?, expires_at = ? WHERE id_hash = ?",
+        )
+        .bind(id_hash(&session.id))
+        .bind(session.last_access)
+        .bind(self.lifetime.expires_at(&session))
+        .bind(id_hash(id))
+        .execute(&self.pool)
+        .await;
+        match result {
+            Ok(done) if done.rows_affected() == 1 => Some(session),
+            Ok(_) => None,
+            Err(e) => {
+                log_store_error("rotate", e);
+                None
+            }
+        }
+    }
+
+    async fn destroy(&self, id: &str) {
+        let result = sqlx::query("DELETE FROM sessions WHERE id_hash = ?")
+            .bind(id_hash(id))
+            .execute(&self.pool)
+            .await;
+        if let Err(e) = result {
+            log_store_error("destroy", e);
+        }
+    }
+
+    async fn destroy_where(&self, key: &str, value: &str) -> usize {
+        // `data` maps keys to JSON text, which json_each yields as-is
+        let result = sqlx::query(
+            "DELETE FROM sessions WHERE EXISTS \
+             (SELECT 1 FROM json_each(sessions.data) WHERE key = ? AND value = ?)",
+        )
+        .bind(key)
+        .bind(value)
+        .execute(&self.pool)
+        .await;
+        match result {
+            Ok(done) => done.rows_affected() as usize,
+            Err(e) => {
+                log_store_error("destroy_where", e);
+                0
+            }
+        }
+    }
+
+    async fn cleanup_expired(&self) -> usize {
+        let result = sqlx::query("DELETE FROM sessions WHERE expires_at <= ?")
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await;
+        match result {
+            Ok(done) => done.rows_affected() as usize,
+            Err(e) => {
+                log_store_error("cleanup_expired", e);
+                0
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db;
+
+    #[tokio::test]
+    async fn test_sqlite_sessions_outlive_the_store() {
+        let pool = db::init_memory_pool().await.unwrap();
+        let store = SqliteSessionStore::new(pool.clone(), SessionLifetime::default(), None);
+        let session = store.create().await;
+        store.update_csrf(&session.id, "token").await;
+        let current = CurrentSession::new(session.clone(), true);
+        current.set("visits", &3u32).unwrap();
+        store
+            .save_data(&session.id, &current.take_changes().unwrap())
+            .await;
+        assert!(current.take_changes().is_none());
+
+        // A new store on the same database, as after a restart
+        let restarted = SqliteSessionStore::new(pool.clone(), SessionLifetime::default(), None);
+        let found = restarted.get(&session.id).await.unwrap();
+        assert_eq!(found.csrf_token, "token");
+        assert_eq!(found.get::<u32>("visits"), Some(3));
+        assert_eq!(found.get::<String>("visits"), None);
+        assert!(restarted.get("not-a-session").await.is_none());
+
+        let (key,): (String,) = sqlx::query_as("SELECT id_hash FROM sessions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_ne!(key, session.id);
+
+        let stale = store.create().await;
+        sqlx::query("UPDATE sessions SET expires_at = ? WHERE id_hash = ?")
+            .bind(Utc::now() - Duration::minutes(1))
+            .bind(id_hash(&stale.id))
+            .execute(&pool)
+            .await
+            .unwrap();
+        assert!(store.get(&stale.id).await.is_none());
+        store.cleanup_expired().await;
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sessions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        restarted.destroy(&session.id).await;
+        assert!(store.get(&session.id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_rotate_and_destroy_everywhere_across_backends() {
+        let sqlite = SqliteSessionStore::new(
+            db::init_memory_pool().await.unwrap(),
+            SessionLifetime::default(),
+            None,
+        );
+        let memory = InMemorySessionStore::default();
+        let stores: [&dyn SessionStore; 2] = [&sqlite, &memory];
+
+        for store in stores {
+            let mut ids = Vec::new();
+            for user in ["alice", "alice", "bob"] {
+                let mut session = store.create().await;
+                session.set("user", &user).unwrap();
+                store.save_data(&session.id, &session.data).await;
+                ids.push(session.id);
+            }
+
+            let rotated = store.rotate(&ids[0]).await.unwrap();
+            assert_ne!(rotated.id, ids[0]);
+            assert_eq!(rotated.get::<String>("user").as_deref(), Some("alice"));
+            assert!(store.get(&ids[0]).await.is_none());
+            assert!(store.rotate(&ids[0]).await.is_none());
+
+            let alice = serde_json::to_string("alice").unwrap();
+            assert_eq!(store.destroy_where("user", &alice).await, 2);
+            assert!(store.get(&rotated.id).await.is_none());
+            assert!(store.get(&ids[1]).await.is_none());
+            assert!(store.get(&ids[2]).await.is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lifetime_bounds_idle_total_and_remembered_sessions() {
+        let lifetime = SessionLifetime {
+            idle: Duration::minutes(30),
+            absolute: Duration::hours(8),
+            remember: Some(Duration::days(30)),
+        };
+        let now = Utc::now();
+        let mut session = Session::new();
+        session.created_at = now - Duration::hours(2);
+        session.last_access = now - Duration::minutes(10);
+        assert_eq!(
+            lifetime.expires_at(&session),
+            session.last_access + Duration::minutes(30)
+        );
+
+        // Busy, but nearly at the absolute limit
+        session.created_at = now - Duration::hours(8) + Duration::minutes(5);
+        assert_eq!(
+            lifetime.expires_at(&session),
+            session.created_at + Duration::hours(8)
+        );
+        session.last_access = now - Duration::hours(1);
+        assert!(session.is_expired(&lifetime));
+        session.remember = true;
+        assert!(!session.is_expired(&lifetime));
+        assert_eq!(
+            lifetime.expires_at(&session),
+            session.created_at + Duration::days(30)
+        );
+        // Remember-me not configured: the flag changes nothing
+        let plain = SessionLifetime {
+            remember: None,
+            ..lifetime
+        };
+        assert!(session.is_expired(&plain));
+
+        let pool = db::init_memory_pool().await.unwrap();
+        let sqlite = SqliteSessionStore::new(pool.clone(), lifetime, None);
+        let memory = InMemorySessionStore::new(lifetime, None);
+        let stores: [&dyn SessionStore; 2] = [&sqlite, &memory];
+        for store in stores {
+            let session = store.create().await;
+            assert!(
+                store
+                    .set_remember(&session.id, true)
+                    .await
+                    .unwrap()
+                    .remember
+            );
+            assert!(store.get(&session.id).await.unwrap().remember);
+            assert!(store.set_remember("not-a-session", true).await.is_none());
+        }
+
+        // A shorter timeout after a restart applies to stored sessions too
+        let strict = SqliteSessionStore::new(
+            pool,
+            SessionLifetime {
+                idle: Duration::zero(),
+                remember: None,
+                ..lifetime
+            },
+            None,
+        );
+        let session = sqlite.create().await;
+        assert!(strict.get(&session.id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_cap_evicts_least_recently_used() {
+        let lifetime = SessionLifetime::default();
+        let sqlite =
+            SqliteSessionStore::new(db::init_memory_pool().await.unwrap(), lifetime, Some(2));
+        let memory = InMemorySessionStore::new(lifetime, Some(2));
+        let stores: [&dyn SessionStore; 2] = [&sqlite, &memory];
+
+        for store in stores {
+            let mut first = store.create().await;
+            let second = store.create().await;
+            // The first is still in use, so the second is least recent
+            store.touch(&mut first).await;
+            let third = store.create().await;
+            assert!(store.get(&first.id).await.is_some());
+            assert!(store.get(&second.id).await.is_none());
+            assert!(store.get(&third.id).await.is_some());
+
+            // A rotated session counts as just used, under its new id
+            let third = store.rotate(&third.id).await.unwrap();
+            let fourth = store.create().await;
+            assert!(store.get(&first.id).await.is_none());
+            assert!(store.get(&third.id).await.is_some());
+            assert!(store.get(&fourth.id).await.is_some());
+        }
     }
 }
@@ -0,0 +1,120 @@
+//! Blob Storage — where uploaded file contents live
+//!
+//! Files are addressed by opaque keys the server generates (never by the
+//! uploaded file name), so a key can't escape the storage root. Metadata
+//! lives in the database; see `attachments`.
+
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::error::{AppError, AppResult};
+
+/// Storage backend trait — local disk by default; swap for S3 etc.
+#[async_trait]
+pub trait BlobStorage: Send + Sync {
+    async fn put(&self, key: &str, bytes: &[u8]) -> AppResult<()>;
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>>;
+    /// Remove a blob; removing one that doesn't exist is not an error
+    async fn delete(&self, key: &str) -> AppResult<()>;
+}
+
+/// A fresh random storage key
+pub fn new_key() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Keys are generated by `new_key`; anything else is refused outright
+fn checked_key(key: &str) -> AppResult<&str> {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Ok(key)
+    } else {
+        Err(AppError::internal(format!("Invalid storage key {:?}", key)))
+    }
+}
+
+fn storage_error(err: std::io::Error) -> AppError {
+    tracing::error!(error = %err, "blob storage error");
+    AppError::internal(format!("Storage error: {}", err))
+}
+
+/// Blobs as files in one local directory, created on first write
+pub struct LocalDiskStorage {
+    root: PathBuf,
+}
+
+impl LocalDiskStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> AppResult<PathBuf> {
+        Ok(self.root.join(checked_key(key)?))
+    }
+}
+
+#[async_trait]
+impl BlobStorage for LocalDiskStorage {
+    async fn put(&self, key: &str, bytes: &[u8]) -> AppResult<()> {
+        let path = self.path(key)?;
+        tokio::fs::create_dir_all(&self.root)
+            .await
+            .map_err(storage_error)?;
+        tokio::fs::write(path, bytes).await.map_err(storage_error)
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        let path = self.path(key)?;
+        tokio::fs::read(path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => AppError::not_found("File"),
+            _ => storage_error(e),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(path).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(storage_error(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// In-memory blobs (prototyping, tests)
+#[derive(Default)]
+pub struct InMemoryStorage {
+    blobs: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl InMemoryStorage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlobStorage for InMemoryStorage {
+    async fn put(&self, key: &str, bytes: &[u8]) -> AppResult<()> {
+        let key = checked_key(key)?;
+        self.blobs
+            .write()
+            .unwrap()
+            .insert(key.to_string(), bytes.to_vec());
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> AppResult<Vec<u8>> {
+        self.blobs
+            .read()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| AppError::not_found("File"))
+    }
+
+    async fn delete(&self, key: &str) -> AppResult<()> {
+        self.blobs.write().unwrap().remove(key);
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+//! Fixtures shared by the unit tests
+
+use std::sync::Arc;
+
+use crate::db;
+use crate::models::AppState;
+use crate::services::Services;
+
+/// Default services over a fresh, migrated in-memory database
+pub(crate) async fn test_state() -> Arc<AppState> {
+    Arc::new(AppState::new(
+        Services::new_default(std::time::SystemTime::now()),
+        db::init_memory_pool().await.unwrap(),
+    ))
+}
@@ -0,0 +1,159 @@
+//! Template filters shared by both render paths
+//!
+//! Askama resolves `{{ value|name }}` to `filters::name` in the module that
+//! derives the template, so modules defining templates import this module in
+//! release builds. Debug builds register the same filters with minijinja,
+//! where values arrive in their serialized (JSON) form.
+
+use chrono::{DateTime, Utc};
+
+#[cfg(debug_assertions)]
+use super::markdown::render_markdown;
+
+/// Human-friendly age of a timestamp relative to `now`, e.g. "3 days ago".
+/// Anything older than a month is shown as a date instead.
+pub fn time_ago(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - at).num_seconds();
+    let (n, unit) = match secs {
+        s if s < 60 => return "just now".to_string(),
+        s if s < 3600 => (s / 60, "minute"),
+        s if s < 86_400 => (s / 3600, "hour"),
+        s if s < 30 * 86_400 => (s / 86_400, "day"),
+        _ => return format!("on {}", at.format("%Y-%m-%d")),
+    };
+    let plural = if n == 1 { "" } else { "s" };
+    format!("{} {}{} ago", n, unit, plural)
+}
+
+/// Urgency of a due date, used as a badge modifier: `"overdue"` once past,
+/// `"today"` until the end of its (UTC) day, `"later"` otherwise or when the
+/// item is already done, and `""` for no due date
+pub fn due_state(due: Option<DateTime<Utc>>, done: bool, now: DateTime<Utc>) -> &'static str {
+    match due {
+        None => "",
+        Some(_) if done => "later",
+        Some(at) if at < now => "overdue",
+        Some(at) if at.date_naive() == now.date_naive() => "today",
+        Some(_) => "later",
+    }
+}
+
+/// A due date for display, e.g. "2025-01-31 14:30 UTC"
+pub fn due_date(due: Option<DateTime<Utc>>) -> String {
+    due.map(|at| at.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+/// A byte count for display, e.g. "512 B", "1.5 KiB", "3.2 MiB"
+pub fn file_size(bytes: u64) -> String {
+    match bytes {
+        b if b < 1024 => format!("{} B", b),
+        b if b < 1024 * 1024 => format!("{:.1} KiB", b as f64 / 1024.0),
+        b => format!("{:.1} MiB", b as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+/// `{{ item.created_at|timeago }}` — askama version
+#[cfg(not(debug_assertions))]
+pub fn timeago(at: &DateTime<Utc>) -> askama::Result<String> {
+    Ok(time_ago(*at, Utc::now()))
+}
+
+/// `{{ item.due_at|duestate(item.done) }}` — askama version
+#[cfg(not(debug_assertions))]
+pub fn duestate(due: &Option<DateTime<Utc>>, done: &bool) -> askama::Result<&'static str> {
+    Ok(due_state(*due, *done, Utc::now()))
+}
+
+/// `{{ item.due_at|duedate }}` — askama version
+#[cfg(not(debug_assertions))]
+pub fn duedate(due: &Option<DateTime<Utc>>) -> askama::Result<String> {
+    Ok(due_date(*due))
+}
+
+/// `{{ attachment.size|filesize }}` — askama version
+#[cfg(not(debug_assertions))]
+pub fn filesize(bytes: &u64) -> askama::Result<String> {
+    Ok(file_size(*bytes))
+}
+
+/// `{{ item.description|md|safe }}` — askama version (askama reserves the
+/// name `markdown` for its own filter). The output is sanitized HTML;
+/// templates still add `safe` so both engines agree.
+#[cfg(not(debug_assertions))]
+pub fn md(source: &str) -> askama::Result<String> {
+    Ok(super::markdown::render_markdown(source))
+}
+
+/// Register the minijinja versions of every filter
+#[cfg(debug_assertions)]
+pub fn register(env: &mut minijinja::Environment) {
+    env.add_filter("timeago", |value: String| {
+        DateTime::parse_from_rfc3339(&value)
+            .map(|at| time_ago(at.with_timezone(&Utc), Utc::now()))
+            .map_err(invalid_date)
+    });
+    env.add_filter("duestate", |value: Option<String>, done: bool| {
+        parse_optional(value).map(|due| due_state(due, done, Utc::now()))
+    });
+    env.add_filter("duedate", |value: Option<String>| {
+        parse_optional(value).map(due_date)
+    });
+    env.add_filter("filesize", file_size);
+    env.add_filter("md", |value: String| {
+        minijinja::Value::from_safe_string(render_markdown(&value))
+    });
+}
+
+#[cfg(debug_assertions)]
+fn parse_optional(value: Option<String>) -> Result<Option<DateTime<Utc>>, minijinja::Error> {
+    value
+        .map(|v| DateTime::parse_from_rfc3339(&v).map(|at| at.with_timezone(&Utc)))
+        .transpose()
+        .map_err(invalid_date)
+}
+
+#[cfg(debug_assertions)]
+fn invalid_date(err: chrono::ParseError) -> minijinja::Error {
+    minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_time_ago_buckets() {
+        let now = Utc::now();
+        assert_eq!(time_ago(now - Duration::seconds(5), now), "just now");
+        assert_eq!(time_ago(now + Duration::seconds(5), now), "just now");
+        assert_eq!(time_ago(now - Duration::minutes(1), now), "1 minute ago");
+        assert_eq!(time_ago(now - Duration::hours(5), now), "5 hours ago");
+        assert_eq!(time_ago(now - Duration::days(3), now), "3 days ago");
+        let old = now - Duration::days(90);
+        assert_eq!(time_ago(old, now), format!("on {}", old.format("%Y-%m-%d")));
+    }
+
+    #[test]
+    fn test_due_state() {
+        let now = "2025-03-10T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(due_state(None, false, now), "");
+        assert_eq!(
+            due_state(Some(now - Duration::minutes(1)), false, now),
+            "overdue"
+        );
+        assert_eq!(
+            due_state(Some(now - Duration::minutes(1)), true, now),
+            "later"
+        );
+        assert_eq!(
+            due_state(Some(now + Duration::hours(11)), false, now),
+            "today"
+        );
+        assert_eq!(
+            due_state(Some(now + Duration::hours(12)), false, now),
+            "later"
+        );
+    }
+}
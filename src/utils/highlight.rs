@@ -0,0 +1,130 @@
+//! Search-term highlighting for templates
+//!
+//! Text is split into plain and matched segments instead of being wrapped in
+//! markup here, so templates keep auto-escaping every byte of user content
+//! and only the `<mark>` tags come from the template itself.
+
+use serde::Serialize;
+
+/// A run of text that either matched a search term or didn't
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Segment {
+    pub text: String,
+    pub hit: bool,
+}
+
+/// Split a free-text query into lowercase alphanumeric search terms.
+/// Punctuation and FTS operators are dropped, so the result is always safe
+/// to embed in an FTS5 `MATCH` expression.
+pub fn search_terms(query: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in query.split(|c: char| !c.is_alphanumeric()) {
+        let word = word.to_lowercase();
+        if !word.is_empty() && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// Highlight word-prefix matches of `terms` in `text` (case-insensitive),
+/// mirroring the `"term"*` prefix queries used for FTS5.
+pub fn highlight(text: &str, terms: &[String]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    let mut push = |text: &str, hit: bool| match segments.last_mut() {
+        Some(last) if last.hit == hit => last.text.push_str(text),
+        _ => segments.push(Segment {
+            text: text.to_string(),
+            hit,
+        }),
+    };
+
+    let mut pos = 0;
+    let mut prev_alnum = false;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let at_word_start = !prev_alnum;
+        let matched = if at_word_start {
+            terms.iter().filter_map(|t| prefix_len(rest, t)).max()
+        } else {
+            None
+        };
+
+        match matched {
+            Some(len) => {
+                push(&rest[..len], true);
+                prev_alnum = rest[..len]
+                    .chars()
+                    .last()
+                    .is_some_and(char::is_alphanumeric);
+                pos += len;
+            }
+            None => {
+                let c = rest.chars().next().unwrap();
+                push(&rest[..c.len_utf8()], false);
+                prev_alnum = c.is_alphanumeric();
+                pos += c.len_utf8();
+            }
+        }
+    }
+
+    segments
+}
+
+/// Byte length of the prefix of `text` matching `term` case-insensitively
+fn prefix_len(text: &str, term: &str) -> Option<usize> {
+    let mut len = 0;
+    let mut chars = text.chars();
+    for t in term.chars() {
+        let c = chars.next()?;
+        if !c.to_lowercase().eq(t.to_lowercase()) {
+            return None;
+        }
+        len += c.len_utf8();
+    }
+    (len > 0).then_some(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(segments: &[Segment]) -> String {
+        segments
+            .iter()
+            .map(|s| {
+                if s.hit {
+                    format!("[{}]", s.text)
+                } else {
+                    s.text.clone()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_terms_strip_operators() {
+        assert_eq!(
+            search_terms(r#"Deploy" OR *prod* deploy"#),
+            vec!["deploy", "or", "prod"]
+        );
+        assert!(search_terms("  \"*  ").is_empty());
+    }
+
+    #[test]
+    fn test_highlight_word_prefixes_only() {
+        let terms = search_terms("dep");
+        assert_eq!(
+            render(&highlight("Deploy the independent depot", &terms)),
+            "[Dep]loy the independent [dep]ot"
+        );
+    }
+
+    #[test]
+    fn test_highlight_keeps_markup_as_plain_text() {
+        let terms = search_terms("script");
+        let segments = highlight("<script>alert(1)</script>", &terms);
+        assert_eq!(render(&segments), "<[script]>alert(1)</[script]>");
+        assert!(segments.iter().all(|s| !s.text.is_empty()));
+    }
+}
@@ -0,0 +1,111 @@
+//! Markdown rendering for user-written text
+//!
+//! Item descriptions are Markdown. They are rendered on the server and the
+//! resulting HTML goes through an allow-list sanitizer before any template
+//! marks it safe:
+//! - raw HTML in the source is shown as text, never interpreted
+//! - only Markdown's own tags and attributes survive
+//! - links get `rel="noopener noreferrer"`; only http(s) and mailto schemes
+//! - images must be same-site paths (no external resources)
+
+use ammonia::{Builder, UrlRelative};
+use pulldown_cmark::{html, Event, Options, Parser};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Tags Markdown (with tables and strikethrough) can produce
+const ALLOWED_TAGS: &[&str] = &[
+    "p",
+    "br",
+    "hr",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "strong",
+    "em",
+    "del",
+    "code",
+    "pre",
+    "blockquote",
+    "ul",
+    "ol",
+    "li",
+    "a",
+    "img",
+    "table",
+    "thead",
+    "tbody",
+    "tr",
+    "th",
+    "td",
+];
+
+static SANITIZER: LazyLock<Builder<'static>> = LazyLock::new(|| {
+    let mut builder = Builder::empty();
+    builder
+        .tags(ALLOWED_TAGS.iter().copied().collect())
+        .tag_attributes(
+            [
+                ("a", ["href", "title"].into_iter().collect()),
+                ("img", ["src", "alt", "title"].into_iter().collect()),
+                ("ol", ["start"].into_iter().collect::<HashSet<_>>()),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .url_schemes(["http", "https", "mailto"].into_iter().collect())
+        .url_relative(UrlRelative::PassThrough)
+        .link_rel(Some("noopener noreferrer"))
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("img", "src") if !is_local_path(value) => None,
+            _ => Some(value.into()),
+        });
+    builder
+});
+
+/// Same-site absolute path, e.g. `/attachments/3` — not `//host/x` or
+/// `/\host/x`, which browsers resolve to another host
+fn is_local_path(url: &str) -> bool {
+    url.starts_with('/') && !url.starts_with("//") && !url.starts_with("/\\")
+}
+
+/// Render Markdown to sanitized HTML, safe to embed without escaping
+pub fn render_markdown(source: &str) -> String {
+    let options = Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH;
+    // Raw HTML is passed through as literal text (escaped by the renderer)
+    let events = Parser::new_ext(source, options).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+    let mut unsafe_html = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut unsafe_html, events);
+    SANITIZER.clean(&unsafe_html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_is_rendered_and_sanitized() {
+        let html = render_markdown("- one\n- **two**\n\n`code`");
+        assert!(html.contains("<li><strong>two</strong></li>"));
+        assert!(html.contains("<code>code</code>"));
+
+        let html = render_markdown("<script>alert(1)</script> <b onclick=x>hi</b>");
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("<b"));
+        assert!(html.contains("&lt;script&gt;"));
+
+        let html = render_markdown("[site](https://example.com) [bad](javascript:alert(1))");
+        assert!(html.contains(r#"<a href="https://example.com" rel="noopener noreferrer">"#));
+        assert!(!html.contains("javascript:"));
+
+        let html = render_markdown("![local](/attachments/1) ![remote](https://evil.example/t.png) ![sneaky](//evil.example/t.png)");
+        assert!(html.contains(r#"src="/attachments/1""#));
+        assert!(!html.contains("evil.example"));
+    }
+}
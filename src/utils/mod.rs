@@ -1,2 +1,5 @@
+pub mod filters;
+pub mod highlight;
 pub mod logging;
+pub mod markdown;
 pub mod templates;
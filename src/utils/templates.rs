@@ -8,6 +8,7 @@ use serde::Serialize;
 pub fn render_template<T: Serialize>(name: &str, context: T) -> Result<String, String> {
     let mut env = Environment::new();
     env.set_loader(minijinja::path_loader("templates"));
+    crate::utils::filters::register(&mut env);
 
     let template = env
         .get_template(name)